@@ -0,0 +1,228 @@
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+// Where we keep local state (answers, inputs, ...), overridable so tests and
+// other checkouts don't trample each other
+pub fn data_dir() -> PathBuf {
+    match env::var("AOC_DATA_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(".aoc"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Incorrect,
+    TooHigh,
+    TooLow,
+}
+
+impl Verdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::Incorrect => "incorrect",
+            Verdict::TooHigh => "too-high",
+            Verdict::TooLow => "too-low",
+        }
+    }
+
+    pub fn parse(input: &str) -> Option<Verdict> {
+        match input {
+            "correct" => Some(Verdict::Correct),
+            "incorrect" => Some(Verdict::Incorrect),
+            "too-high" => Some(Verdict::TooHigh),
+            "too-low" => Some(Verdict::TooLow),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+// Every answer we've ever submitted and what the server said about it. On
+// disk it's one tab separated record per line: day, part, verdict, answer.
+#[derive(Debug)]
+pub struct AnswerDb {
+    path: PathBuf,
+    records: Vec<Record>,
+}
+
+impl AnswerDb {
+    pub fn open(path: &Path) -> Result<AnswerDb, String> {
+        let mut db = AnswerDb {
+            path: path.to_path_buf(),
+            records: Vec::new(),
+        };
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            // Nothing submitted yet. Anything else has to stop us, or the next
+            // save would replace the whole history.
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(db),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+
+        for (idx, line) in contents.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let parts: Vec<&str> = line.splitn(4, '\t').collect();
+            if parts.len() != 4 {
+                return Err(format!("{}:{}: malformed record", path.display(), idx + 1));
+            }
+            let day = parts[0].parse::<u8>();
+            let part = parts[1].parse::<u8>();
+            let verdict = Verdict::parse(parts[2]);
+            match (day, part, verdict) {
+                (Ok(day), Ok(part), Some(verdict)) => db.records.push(Record {
+                    day,
+                    part,
+                    answer: parts[3].to_string(),
+                    verdict,
+                }),
+                _ => return Err(format!("{}:{}: malformed record", path.display(), idx + 1)),
+            }
+        }
+
+        Ok(db)
+    }
+
+    pub fn open_default() -> Result<AnswerDb, String> {
+        AnswerDb::open(&data_dir().join("answers.tsv"))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        let mut out = String::new();
+        for record in &self.records {
+            out += &format!(
+                "{}\t{}\t{}\t{}\n",
+                record.day,
+                record.part,
+                record.verdict.as_str(),
+                record.answer
+            );
+        }
+        fs::write(&self.path, out).map_err(|e| e.to_string())
+    }
+
+    pub fn record(&mut self, day: u8, part: u8, answer: &str, verdict: Verdict) {
+        self.records.push(Record {
+            day,
+            part,
+            answer: answer.trim().to_string(),
+            verdict,
+        });
+    }
+
+    pub fn records(&self, day: u8, part: u8) -> Vec<&Record> {
+        self.records
+            .iter()
+            .filter(|r| r.day == day && r.part == part)
+            .collect()
+    }
+
+    pub fn correct_answer(&self, day: u8, part: u8) -> Option<&str> {
+        self.records(day, part)
+            .into_iter()
+            .find(|r| r.verdict == Verdict::Correct)
+            .map(|r| r.answer.as_str())
+    }
+
+    // Returns the record that proves this answer wrong, either because we
+    // already submitted it or because a too-high/too-low hint rules it out
+    pub fn known_wrong(&self, day: u8, part: u8, answer: &str) -> Option<&Record> {
+        let answer = answer.trim();
+        let numeric = answer.parse::<i128>().ok();
+
+        for record in self.records(day, part) {
+            if record.verdict == Verdict::Correct {
+                continue;
+            }
+            if record.answer == answer {
+                return Some(record);
+            }
+
+            let (Some(num), Ok(bound)) = (numeric, record.answer.parse::<i128>()) else {
+                continue;
+            };
+            match record.verdict {
+                Verdict::TooHigh if num >= bound => return Some(record),
+                Verdict::TooLow if num <= bound => return Some(record),
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("aoc2021-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn known_wrong_uses_hints() {
+        let mut db = AnswerDb::open(&temp_path("missing.tsv")).unwrap();
+        db.record(3, 1, "100", Verdict::TooHigh);
+        db.record(3, 1, "10", Verdict::TooLow);
+        db.record(3, 1, "abc", Verdict::Incorrect);
+
+        assert_eq!(
+            db.known_wrong(3, 1, "100").unwrap().verdict,
+            Verdict::TooHigh
+        );
+        assert_eq!(
+            db.known_wrong(3, 1, "150").unwrap().verdict,
+            Verdict::TooHigh
+        );
+        assert_eq!(db.known_wrong(3, 1, "9").unwrap().verdict, Verdict::TooLow);
+        assert_eq!(
+            db.known_wrong(3, 1, "abc").unwrap().verdict,
+            Verdict::Incorrect
+        );
+        assert_eq!(db.known_wrong(3, 1, "50"), None);
+        assert_eq!(db.known_wrong(3, 2, "100"), None);
+    }
+
+    #[test]
+    fn round_trips_through_disk() {
+        let path = temp_path("answers.tsv");
+        let mut db = AnswerDb::open(&path).unwrap();
+        db.record(1, 2, "1234", Verdict::Correct);
+        db.record(1, 1, "99", Verdict::TooLow);
+        db.save().unwrap();
+
+        let db = AnswerDb::open(&path).unwrap();
+        assert_eq!(db.correct_answer(1, 2), Some("1234"));
+        assert_eq!(db.records(1, 1)[0].verdict, Verdict::TooLow);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn refuses_unreadable_files() {
+        let path = temp_path("binary.tsv");
+        fs::write(&path, b"1\t1\tcorrect\t\xff\n").unwrap();
+        assert!(AnswerDb::open(&path).is_err());
+        fs::remove_file(&path).unwrap();
+
+        assert!(AnswerDb::open(&env::temp_dir()).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct Timing {
+    pub label: String,
+    pub runs: u32,
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    pub fn mean(&self) -> Duration {
+        self.total / self.runs.max(1)
+    }
+}
+
+impl fmt::Display for Timing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} runs, mean {:?}, min {:?}, max {:?}",
+            self.label,
+            self.runs,
+            self.mean(),
+            self.min,
+            self.max
+        )
+    }
+}
+
+// Runs `f` a number of times and keeps the result of the last run so the
+// work can't be optimised away
+pub fn measure<T>(label: &str, runs: u32, mut f: impl FnMut() -> T) -> (T, Timing) {
+    let mut timing = Timing {
+        label: label.to_string(),
+        runs: 0,
+        total: Duration::ZERO,
+        min: Duration::MAX,
+        max: Duration::ZERO,
+    };
+
+    let mut rv = None;
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        rv = Some(std::hint::black_box(f()));
+        let elapsed = start.elapsed();
+
+        timing.runs += 1;
+        timing.total += elapsed;
+        timing.min = timing.min.min(elapsed);
+        timing.max = timing.max.max(elapsed);
+    }
+
+    (rv.unwrap(), timing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_runs() {
+        let mut calls = 0;
+        let (rv, timing) = measure("count", 3, || {
+            calls += 1;
+            calls
+        });
+        assert_eq!(rv, 3);
+        assert_eq!(timing.runs, 3);
+        assert!(timing.min <= timing.max);
+    }
+}
@@ -0,0 +1,404 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+use std::str::FromStr;
+
+// Arbitrary precision unsigned integer for the counting puzzles whose
+// answers blow past u64 once the step counts get silly. Stored as base 2^32
+// limbs, least significant first, with no trailing zero limbs so that the
+// derived equality and hashing work.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> BigUint {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub fn one() -> BigUint {
+        BigUint::from(1u64)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs.len() {
+            0 => Some(0),
+            1 => Some(self.limbs[0] as u64),
+            2 => Some(self.limbs[0] as u64 | (self.limbs[1] as u64) << 32),
+            _ => None,
+        }
+    }
+
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub fn pow(&self, mut exp: u32) -> BigUint {
+        let mut rv = BigUint::one();
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                rv = &rv * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        rv
+    }
+
+    // Remainder by a machine sized modulus, handy for "answer mod m" output
+    pub fn rem_u64(&self, modulus: u64) -> u64 {
+        assert!(modulus != 0, "modulus must not be zero");
+        let mut rv: u128 = 0;
+        for limb in self.limbs.iter().rev() {
+            rv = ((rv << 32) | *limb as u128) % modulus as u128;
+        }
+        rv as u64
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    fn mul_small(&mut self, factor: u32) {
+        let mut carry: u64 = 0;
+        for limb in self.limbs.iter_mut() {
+            let value = *limb as u64 * factor as u64 + carry;
+            *limb = value as u32;
+            carry = value >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+        self.trim();
+    }
+
+    fn add_small(&mut self, value: u32) {
+        let mut carry = value as u64;
+        for limb in self.limbs.iter_mut() {
+            if carry == 0 {
+                return;
+            }
+            let sum = *limb as u64 + carry;
+            *limb = sum as u32;
+            carry = sum >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+
+    // Divides in place and returns the remainder
+    fn div_small(&mut self, divisor: u32) -> u32 {
+        let mut rem: u64 = 0;
+        for limb in self.limbs.iter_mut().rev() {
+            let value = (rem << 32) | *limb as u64;
+            *limb = (value / divisor as u64) as u32;
+            rem = value % divisor as u64;
+        }
+        self.trim();
+        rem as u32
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> BigUint {
+        let mut rv = BigUint {
+            limbs: vec![value as u32, (value >> 32) as u32],
+        };
+        rv.trim();
+        rv
+    }
+}
+
+impl From<u32> for BigUint {
+    fn from(value: u32) -> BigUint {
+        BigUint::from(value as u64)
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(value: u128) -> BigUint {
+        let mut rv = BigUint {
+            limbs: (0..4).map(|i| (value >> (32 * i)) as u32).collect(),
+        };
+        rv.trim();
+        rv
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<BigUint, String> {
+        if input.is_empty() {
+            return Err("empty number".to_string());
+        }
+        let mut rv = BigUint::zero();
+        for chr in input.chars() {
+            let digit = chr
+                .to_digit(10)
+                .ok_or(format!("invalid digit {:?} in {}", chr, input))?;
+            rv.mul_small(10);
+            rv.add_small(digit);
+        }
+        Ok(rv)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad("0");
+        }
+
+        // Peel off nine decimal digits at a time
+        let mut chunks = Vec::new();
+        let mut value = self.clone();
+        while !value.is_zero() {
+            chunks.push(value.div_small(1_000_000_000));
+        }
+
+        let mut rv = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            rv += &format!("{:09}", chunk);
+        }
+        f.pad(&rv)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, other: &BigUint) {
+        if self.limbs.len() < other.limbs.len() {
+            self.limbs.resize(other.limbs.len(), 0);
+        }
+        let mut carry: u64 = 0;
+        for idx in 0..self.limbs.len() {
+            let sum = self.limbs[idx] as u64 + *other.limbs.get(idx).unwrap_or(&0) as u64 + carry;
+            self.limbs[idx] = sum as u32;
+            carry = sum >> 32;
+            if carry == 0 && idx >= other.limbs.len() {
+                break;
+            }
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+}
+
+impl AddAssign for BigUint {
+    fn add_assign(&mut self, other: BigUint) {
+        *self += &other;
+    }
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let mut rv = self.clone();
+        rv += other;
+        rv
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(mut self, other: BigUint) -> BigUint {
+        self += &other;
+        self
+    }
+}
+
+// Like the primitive types, going below zero is a bug and panics
+impl SubAssign<&BigUint> for BigUint {
+    fn sub_assign(&mut self, other: &BigUint) {
+        assert!(*self >= *other, "attempt to subtract with overflow");
+        let mut borrow: i64 = 0;
+        for idx in 0..self.limbs.len() {
+            let mut diff =
+                self.limbs[idx] as i64 - *other.limbs.get(idx).unwrap_or(&0) as i64 - borrow;
+            borrow = 0;
+            if diff < 0 {
+                diff += 1 << 32;
+                borrow = 1;
+            }
+            self.limbs[idx] = diff as u32;
+            if borrow == 0 && idx >= other.limbs.len() {
+                break;
+            }
+        }
+        self.trim();
+    }
+}
+
+impl SubAssign for BigUint {
+    fn sub_assign(&mut self, other: BigUint) {
+        *self -= &other;
+    }
+}
+
+impl Sub<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn sub(self, other: &BigUint) -> BigUint {
+        let mut rv = self.clone();
+        rv -= other;
+        rv
+    }
+}
+
+impl Sub for BigUint {
+    type Output = BigUint;
+
+    fn sub(mut self, other: BigUint) -> BigUint {
+        self -= &other;
+        self
+    }
+}
+
+impl Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+
+        // Schoolbook, the numbers here are a few hundred digits at most
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry: u64 = 0;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let value = *a as u64 * *b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = value as u32;
+                carry = value >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+
+        let mut rv = BigUint { limbs };
+        rv.trim();
+        rv
+    }
+}
+
+impl Mul for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: BigUint) -> BigUint {
+        &self * &other
+    }
+}
+
+impl MulAssign<&BigUint> for BigUint {
+    fn mul_assign(&mut self, other: &BigUint) {
+        *self = &*self * other;
+    }
+}
+
+impl Sum for BigUint {
+    fn sum<I: Iterator<Item = BigUint>>(iter: I) -> BigUint {
+        let mut rv = BigUint::zero();
+        for value in iter {
+            rv += &value;
+        }
+        rv
+    }
+}
+
+impl<'a> Sum<&'a BigUint> for BigUint {
+    fn sum<I: Iterator<Item = &'a BigUint>>(iter: I) -> BigUint {
+        let mut rv = BigUint::zero();
+        for value in iter {
+            rv += value;
+        }
+        rv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(input: &str) -> BigUint {
+        input.parse::<BigUint>().unwrap()
+    }
+
+    #[test]
+    fn matches_u128() {
+        let values: [u128; 6] = [
+            0,
+            1,
+            4294967295,
+            4294967296,
+            18446744073709551615,
+            99999999999,
+        ];
+        for a in values {
+            for b in values {
+                let (ba, bb) = (big(&a.to_string()), big(&b.to_string()));
+                assert_eq!((&ba + &bb).to_string(), (a + b).to_string());
+                if a <= b {
+                    // Only safe below 2^64 * 2^64
+                    assert_eq!((&ba * &bb).to_string(), (a * b).to_string());
+                    assert_eq!((&bb - &ba).to_string(), (b - a).to_string());
+                }
+                assert_eq!(ba.cmp(&bb), a.cmp(&b));
+            }
+        }
+    }
+
+    #[test]
+    fn handles_huge_numbers() {
+        let two = BigUint::from(2u64);
+        assert_eq!(
+            two.pow(200).to_string(),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+        assert_eq!(two.pow(200).bits(), 201);
+        assert_eq!(two.pow(64) - BigUint::one(), BigUint::from(u64::MAX));
+        assert_eq!(two.pow(100).rem_u64(1_000_000_007), 976371285);
+        assert_eq!(BigUint::from(u64::MAX).to_u64(), Some(u64::MAX));
+        assert_eq!(two.pow(64).to_u64(), None);
+        assert_eq!(BigUint::from(u128::MAX), two.pow(128) - BigUint::one());
+        assert_eq!(format!("{:>5}", BigUint::from(42u64)), "   42");
+        assert!("12a".parse::<BigUint>().is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_below_zero() {
+        let _ = BigUint::from(1u64) - BigUint::from(2u64);
+    }
+}
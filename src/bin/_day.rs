@@ -1,6 +1,10 @@
+use aoc2021::text;
+
 struct Struct {}
 
 fn parse_input(input: &str) -> Struct {
+    for _line in text::lines(input) {}
+
     Struct {}
 }
 
@@ -0,0 +1,147 @@
+use std::env;
+use std::fs;
+use std::process::{exit, Command};
+
+use aoc2021::answers::AnswerDb;
+use aoc2021::inputs::InputStore;
+use aoc2021::submit::{submit, Client, Outcome};
+
+fn usage() -> ! {
+    eprintln!("usage: aoc submit <day> <part> [answer]");
+    eprintln!("       aoc inputs import <day> [file]");
+    eprintln!("       aoc inputs check [day]");
+    exit(2);
+}
+
+// Day 1 was two separate programs that print ANSWER, everything else prints
+// PART ONE/PART TWO from a single binary
+fn day_binary(day: u8, part: u8) -> (String, &'static str) {
+    match (day, part) {
+        (1, 1) => ("day1a".to_string(), "ANSWER: "),
+        (1, _) => ("day1b".to_string(), "ANSWER: "),
+        (_, 1) => (format!("day{}", day), "PART ONE: "),
+        _ => (format!("day{}", day), "PART TWO: "),
+    }
+}
+
+fn compute_answer(day: u8, part: u8) -> Result<String, String> {
+    let (bin, prefix) = day_binary(day, part);
+    let cargo = env::var("CARGO").unwrap_or("cargo".to_string());
+    let output = Command::new(cargo)
+        .args(["run", "--quiet", "--release", "--bin", &bin])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            bin,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .find_map(|l| l.strip_prefix(prefix))
+        .map(|a| a.trim().to_string())
+        .ok_or(format!("{} didn't print an answer", bin))
+}
+
+fn run_submit(args: &[String]) -> Result<(), String> {
+    if args.len() < 2 || args.len() > 3 {
+        usage();
+    }
+    let day = parse_day(&args[0])?;
+    let part = args[1].parse::<u8>().map_err(|_| "bad part")?;
+    if !(1..=2).contains(&part) {
+        usage();
+    }
+
+    let answer = match args.get(2) {
+        Some(answer) => answer.clone(),
+        None => compute_answer(day, part)?,
+    };
+
+    let mut db = AnswerDb::open_default()?;
+    let client = Client::from_env();
+    match submit(&mut db, &client, day, part, &answer)? {
+        Outcome::Judged(verdict) => println!("{}: {}", answer, verdict.as_str()),
+        Outcome::AlreadyCorrect(correct) => {
+            println!("day {} part {} already solved with {}", day, part, correct)
+        }
+        Outcome::KnownWrong(verdict) => {
+            return Err(format!(
+                "refusing to resubmit {}, already known wrong ({})",
+                answer,
+                verdict.as_str()
+            ))
+        }
+        Outcome::TooRecent => return Err("answered too recently, try again later".to_string()),
+        Outcome::WrongLevel => return Err("that part isn't open or is already solved".to_string()),
+        Outcome::Unrecognized(body) => return Err(format!("unrecognized response: {}", body)),
+    }
+    Ok(())
+}
+
+fn parse_day(arg: &str) -> Result<u8, String> {
+    match arg.parse::<u8>() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(format!("bad day {}", arg)),
+    }
+}
+
+fn run_inputs(args: &[String]) -> Result<(), String> {
+    let store = InputStore::from_env();
+
+    match (args.first().map(|s| s.as_str()), args.len()) {
+        (Some("import"), 2 | 3) => {
+            let day = parse_day(&args[1])?;
+            // By default pick up the loose file that sits next to the code
+            let path = match args.get(2) {
+                Some(path) => path.clone(),
+                None => format!("src/bin/day{}.txt", day),
+            };
+            let contents = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+            let digest = store.import(day, &contents)?;
+            println!("day {}: {} ({})", day, store.path(day).display(), digest);
+        }
+        (Some("check"), 1 | 2) => {
+            let days = match args.get(1) {
+                Some(day) => vec![parse_day(day)?],
+                None => store.days(),
+            };
+
+            let mut bad = 0;
+            for day in days {
+                let issues = store.check(day)?;
+                if issues.is_empty() {
+                    println!("day {}: ok", day);
+                    continue;
+                }
+                bad += 1;
+                for issue in issues {
+                    println!("day {}: {}", day, issue.describe());
+                }
+            }
+            if bad > 0 {
+                return Err(format!("{} input(s) need attention", bad));
+            }
+        }
+        _ => usage(),
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(|s| s.as_str()) {
+        Some("submit") => run_submit(&args[1..]),
+        Some("inputs") => run_inputs(&args[1..]),
+        _ => usage(),
+    };
+
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        exit(1);
+    }
+}
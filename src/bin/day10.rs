@@ -1,86 +1,156 @@
-fn part_one(input: &str) -> u32 {
-    let mut rv = 0;
-
-    for line in input.split("\n") {
-        let mut chars: Vec<&str> = Vec::new();
-        for char in line.split("") {
-            match char {
-                "{" | "[" | "<" | "(" => {
-                    chars.push(char);
-                }
-                _ => {
-                    if let Some(test_char) = chars.pop() {
-                        match (test_char.to_owned() + char).as_str() {
-                            "<>" | "[]" | "{}" | "()" => continue,
-                            _ => match char {
-                                ")" => rv += 3,
-                                "]" => rv += 57,
-                                "}" => rv += 1197,
-                                ">" => rv += 25137,
-                                _ => {}
-                            },
-                        }
-                        break;
-                    }
-                }
-            }
-        }
-        dbg!(rv);
-    }
+use aoc2021::text;
+use std::env;
 
-    rv
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pair {
+    open: char,
+    close: char,
+    // What a wrong closer scores, and what a missing one adds to the
+    // completion score
+    corrupted: u64,
+    incomplete: u64,
 }
 
-fn part_two(input: &str) -> u64 {
-    let mut scores: Vec<u64> = Vec::new();
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Check {
+    Valid,
+    // Columns count from 1, expected is None when nothing was open
+    Corrupted {
+        column: usize,
+        expected: Option<char>,
+        found: char,
+    },
+    // Closers needed to finish the line, innermost first
+    Incomplete(Vec<char>),
+}
 
-    for line in input.split("\n") {
-        let mut chars: Vec<&str> = Vec::new();
-        let mut corrupt = true;
+#[derive(Debug, Clone)]
+struct Checker {
+    pairs: Vec<Pair>,
+    // Completion scores are read as digits in this base
+    base: u64,
+}
 
-        for char in line.split("") {
-            match char {
-                "{" | "[" | "<" | "(" => {
-                    chars.push(char);
+impl Checker {
+    fn new(pairs: &[Pair], base: u64) -> Result<Checker, String> {
+        let mut seen = Vec::new();
+        for pair in pairs {
+            for c in [pair.open, pair.close] {
+                if seen.contains(&c) {
+                    return Err(format!("{:?} used twice", c));
                 }
-                _ => {
-                    if let Some(test_char) = chars.pop() {
-                        match (test_char.to_owned() + char).as_str() {
-                            "<>" | "[]" | "{}" | "()" => continue,
-                            _ => match char {
-                                "" => {
-                                    corrupt = false;
-                                    chars.push(test_char);
-                                }
-                                _ => {}
-                            },
-                        }
-                        break;
-                    }
+                seen.push(c);
+            }
+        }
+        Ok(Checker {
+            pairs: pairs.to_vec(),
+            base,
+        })
+    }
+
+    fn standard() -> Checker {
+        let pair = |open, close, corrupted, incomplete| Pair {
+            open,
+            close,
+            corrupted,
+            incomplete,
+        };
+        Checker::new(
+            &[
+                pair('(', ')', 3, 1),
+                pair('[', ']', 57, 2),
+                pair('{', '}', 1197, 3),
+                pair('<', '>', 25137, 4),
+            ],
+            5,
+        )
+        .unwrap()
+    }
+
+    fn check(&self, line: &str) -> Result<Check, String> {
+        let mut open: Vec<&Pair> = Vec::new();
+        for (idx, c) in line.chars().enumerate() {
+            if let Some(pair) = self.pairs.iter().find(|p| p.open == c) {
+                open.push(pair);
+                continue;
+            }
+            if !self.pairs.iter().any(|p| p.close == c) {
+                return Err(format!("column {}: unknown character {:?}", idx + 1, c));
+            }
+            match open.pop() {
+                Some(pair) if pair.close == c => {}
+                other => {
+                    return Ok(Check::Corrupted {
+                        column: idx + 1,
+                        expected: other.map(|p| p.close),
+                        found: c,
+                    })
                 }
             }
         }
 
-        if corrupt {
-            continue;
+        if open.is_empty() {
+            Ok(Check::Valid)
+        } else {
+            Ok(Check::Incomplete(
+                open.iter().rev().map(|p| p.close).collect(),
+            ))
         }
+    }
 
-        let mut score: u64 = 0;
-        while let Some(char) = chars.pop() {
-            score *= 5;
+    fn pair(&self, close: char) -> &Pair {
+        self.pairs.iter().find(|p| p.close == close).unwrap()
+    }
 
-            match char {
-                "(" => score += 1,
-                "[" => score += 2,
-                "{" => score += 3,
-                "<" => score += 4,
-                _ => panic!("oh no"),
-            }
+    // Zero for anything that isn't corrupted
+    fn corrupted_score(&self, check: &Check) -> u64 {
+        match check {
+            Check::Corrupted { found, .. } => self.pair(*found).corrupted,
+            _ => 0,
         }
-        scores.push(score);
     }
 
-    scores.sort_by(|a, b| a.cmp(b));
+    // None for anything that isn't incomplete
+    fn completion_score(&self, check: &Check) -> Option<u64> {
+        match check {
+            Check::Incomplete(missing) => Some(
+                missing
+                    .iter()
+                    .fold(0, |acc, c| acc * self.base + self.pair(*c).incomplete),
+            ),
+            _ => None,
+        }
+    }
+}
+
+fn check_all(checker: &Checker, input: &str) -> Vec<Check> {
+    text::lines(input)
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            checker
+                .check(line)
+                .map_err(|e| format!("line {}: {}", idx + 1, e))
+                .unwrap()
+        })
+        .collect()
+}
+
+fn part_one(input: &str) -> u64 {
+    let checker = Checker::standard();
+    check_all(&checker, input)
+        .iter()
+        .map(|check| checker.corrupted_score(check))
+        .sum()
+}
+
+fn part_two(input: &str) -> u64 {
+    let checker = Checker::standard();
+    let mut scores: Vec<u64> = check_all(&checker, input)
+        .iter()
+        .filter_map(|check| checker.completion_score(check))
+        .collect();
+    scores.sort();
 
     scores[scores.len() / 2]
 }
@@ -89,4 +159,131 @@ fn main() {
     let input = include_str!("day10.txt");
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
+
+    // `check` says what's wrong with every line
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|a| a.as_str()) == Some("check") {
+        for (idx, check) in check_all(&Checker::standard(), input).iter().enumerate() {
+            match check {
+                Check::Valid => println!("LINE {}: valid", idx + 1),
+                Check::Corrupted {
+                    column,
+                    expected,
+                    found,
+                } => println!(
+                    "LINE {}: corrupted at column {}, expected {} but found {:?}",
+                    idx + 1,
+                    column,
+                    expected.map_or("nothing".to_string(), |c| format!("{:?}", c)),
+                    found
+                ),
+                Check::Incomplete(missing) => println!(
+                    "LINE {}: incomplete, missing {}",
+                    idx + 1,
+                    missing.iter().collect::<String>()
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use aoc2021::text::variants;
+
+    const SAMPLE: &str = "[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]";
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), 26397);
+            assert_eq!(part_two(&input), 288957);
+        }
+    }
+
+    #[test]
+    fn locates_errors() {
+        let checker = Checker::standard();
+        let checks = check_all(&checker, SAMPLE);
+        assert_eq!(
+            checks[2],
+            Check::Corrupted {
+                column: 13,
+                expected: Some(']'),
+                found: '}'
+            }
+        );
+        assert_eq!(checks[0], Check::Incomplete("}}]])})]".chars().collect()));
+        assert_eq!(checker.completion_score(&checks[0]), Some(288957));
+        assert_eq!(checker.check("([]<>)"), Ok(Check::Valid));
+        assert_eq!(
+            checker.check("())"),
+            Ok(Check::Corrupted {
+                column: 3,
+                expected: None,
+                found: ')'
+            })
+        );
+        assert_eq!(
+            checker.check("(a)"),
+            Err("column 2: unknown character 'a'".to_string())
+        );
+    }
+
+    #[test]
+    fn checks_other_syntaxes() {
+        // Guillemets and slashes, with a binary completion score
+        let pairs = [
+            Pair {
+                open: '«',
+                close: '»',
+                corrupted: 10,
+                incomplete: 1,
+            },
+            Pair {
+                open: '/',
+                close: '\\',
+                corrupted: 20,
+                incomplete: 0,
+            },
+        ];
+        let checker = Checker::new(&pairs, 2).unwrap();
+        let check = checker.check("«/«\\").unwrap();
+        assert_eq!(
+            check,
+            Check::Corrupted {
+                column: 4,
+                expected: Some('»'),
+                found: '\\'
+            }
+        );
+        assert_eq!(checker.corrupted_score(&check), 20);
+
+        let check = checker.check("«/«»").unwrap();
+        assert_eq!(check, Check::Incomplete(vec!['\\', '»']));
+        assert_eq!(checker.completion_score(&check), Some(0b01));
+        assert_eq!(checker.check(""), Ok(Check::Valid));
+
+        let clash = [
+            pairs[0],
+            Pair {
+                open: '»',
+                ..pairs[1]
+            },
+        ];
+        assert_eq!(
+            Checker::new(&clash, 2).unwrap_err(),
+            "'»' used twice".to_string()
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use aoc2021::text;
 use std::cmp::{max, min};
 use std::collections::HashMap;
 
@@ -102,7 +103,7 @@ fn parse_board(input: &str) -> Board {
 
     let mut width: u32 = 0;
     let mut y: u32 = 0;
-    for line in input.split("\n") {
+    for line in text::lines(input) {
         let mut x: u32 = 0;
         if width == 0 {
             width = line.len() as u32;
@@ -163,3 +164,28 @@ fn main() {
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{part_one, part_two};
+    use aoc2021::text::variants;
+
+    const SAMPLE: &str = "5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526";
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), 1656);
+            assert_eq!(part_two(&input), 195);
+        }
+    }
+}
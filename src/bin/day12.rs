@@ -1,3 +1,4 @@
+use aoc2021::text;
 use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, PartialEq)]
@@ -83,7 +84,7 @@ impl Cave {
 fn parse_input(input: &str) -> HashMap<String, Cave> {
     let mut rv: HashMap<String, Cave> = HashMap::new();
 
-    for line in input.split("\n") {
+    for line in text::lines(input) {
         let parts: Vec<String> = line.split("-").map(|s| s.to_string()).collect();
 
         // Insert the forward path
@@ -125,3 +126,17 @@ fn main() {
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{part_one, part_two};
+    use aoc2021::text::variants;
+
+    #[test]
+    fn it_works() {
+        for input in variants("start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end") {
+            assert_eq!(part_one(&input), 10);
+            assert_eq!(part_two(&input), 36);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use aoc2021::text;
 use std::cmp::{max, min};
 
 #[derive(Debug)]
@@ -107,29 +108,9 @@ fn parse_input(input: &str) -> (Paper, Vec<Fold>) {
     let mut rv = Paper::new();
     let mut folds = Vec::new();
 
-    let mut parsing_folds = false;
-    for line in input.split("\n") {
-        if parsing_folds {
-            // fold along x=5
-            let (left, right) = line.split(" ").collect::<Vec<&str>>()[2]
-                .split_once("=")
-                .unwrap();
-            folds.push(Fold {
-                axis: if left == "x" {
-                    FoldAxis::X
-                } else {
-                    FoldAxis::Y
-                },
-                index: right.parse::<u32>().unwrap(),
-            });
-            continue;
-        }
-
-        if line == "" {
-            parsing_folds = true;
-            continue;
-        }
-
+    // Dots first, then a blank line, then the folds
+    let sections = text::sections(input);
+    for line in &sections[0] {
         let (x, y) = line.split_once(",").unwrap();
         rv.dots.push(Point {
             x: x.parse::<u32>().unwrap(),
@@ -137,6 +118,21 @@ fn parse_input(input: &str) -> (Paper, Vec<Fold>) {
         })
     }
 
+    for line in sections.get(1).unwrap_or(&Vec::new()) {
+        // fold along x=5
+        let (left, right) = line.split(" ").collect::<Vec<&str>>()[2]
+            .split_once("=")
+            .unwrap();
+        folds.push(Fold {
+            axis: if left == "x" {
+                FoldAxis::X
+            } else {
+                FoldAxis::Y
+            },
+            index: right.parse::<u32>().unwrap(),
+        });
+    }
+
     (rv, folds)
 }
 
@@ -163,3 +159,38 @@ fn main() {
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::part_one;
+    use aoc2021::text::variants;
+
+    const SAMPLE: &str = "6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5";
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), 17);
+        }
+    }
+}
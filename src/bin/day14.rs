@@ -1,11 +1,14 @@
+use aoc2021::bigint::BigUint;
+use aoc2021::text;
 use std::cmp::{max, min};
 use std::collections::HashMap;
+use std::env;
 
 #[derive(Debug)]
 struct Insertion {
     pair: [char; 2],
     insert: char,
-    counter: u64,
+    counter: BigUint,
     is_tail: bool,
 }
 
@@ -23,7 +26,7 @@ impl Insertion {
         Insertion {
             pair: chars,
             insert: parts[2].to_string().chars().next().unwrap(),
-            counter: 0,
+            counter: BigUint::zero(),
             is_tail: false,
         }
     }
@@ -82,9 +85,9 @@ fn make_insertions_faster(insertions: &Insertions) -> Insertions {
             pair: insertion.pair,
             insert: insertion.insert,
             counter: if insertion.insert == ' ' {
-                insertion.counter
+                insertion.counter.clone()
             } else {
-                0
+                BigUint::zero()
             },
             is_tail: false,
         });
@@ -93,7 +96,7 @@ fn make_insertions_faster(insertions: &Insertions) -> Insertions {
     // Now the only thing we need to do is handle creating insertions
     // which is based on the rightmost pair
     for insertion in insertions.values() {
-        if insertion.counter == 0 || insertion.insert == ' ' {
+        if insertion.counter.is_zero() || insertion.insert == ' ' {
             // Not a valid insertion, the counter was already preserved in the
             // initialization above, but we need to keep the tail value just
             // in case
@@ -108,10 +111,10 @@ fn make_insertions_faster(insertions: &Insertions) -> Insertions {
         let mut entry = rv.entry(left_pair).or_insert(Insertion {
             pair: left_pair,
             insert: ' ',
-            counter: 0,
+            counter: BigUint::zero(),
             is_tail: false,
         });
-        entry.counter += insertion.counter;
+        entry.counter += &insertion.counter;
         /*println!(
             "(L) {:?} += {} = {} (was tail {})",
             left_pair, insertion.counter, entry.counter, insertion.is_tail
@@ -125,10 +128,10 @@ fn make_insertions_faster(insertions: &Insertions) -> Insertions {
         let mut entry = rv.entry(right_pair).or_insert(Insertion {
             pair: right_pair,
             insert: ' ',
-            counter: 0,
+            counter: BigUint::zero(),
             is_tail: false,
         });
-        entry.counter += insertion.counter;
+        entry.counter += &insertion.counter;
         entry.is_tail |= insertion.is_tail;
         /*println!(
             "(R) {:?} += {} = {} (was tail {}, is tail {})",
@@ -139,16 +142,11 @@ fn make_insertions_faster(insertions: &Insertions) -> Insertions {
 }
 
 fn parse_input(input: &str) -> (String, Insertions) {
-    let mut initial = "";
+    let lines = text::lines(input);
+    let initial = lines[0];
     let mut insertions = Insertions::new();
 
-    for line in input.split("\n") {
-        if initial == "" {
-            initial = line;
-            continue;
-        } else if line == "" {
-            continue;
-        }
+    for line in &lines[1..] {
         let insertion = Insertion::new(line);
         insertions.insert(insertion.pair, insertion);
     }
@@ -161,10 +159,10 @@ fn parse_input(input: &str) -> (String, Insertions) {
         let entry = insertions.entry(pair).or_insert(Insertion {
             pair,
             insert: ' ',
-            counter: 0,
+            counter: BigUint::zero(),
             is_tail: false,
         });
-        entry.counter += 1;
+        entry.counter += BigUint::one();
         entry.is_tail = idx == (chars.len() - 1);
     }
 
@@ -194,10 +192,11 @@ fn part_one(input: &str) -> u32 {
     maxc - minc
 }
 
-fn part_two(input: &str) -> u64 {
+// Counts with big integers so the step count can go well past 40
+fn polymerize(input: &str, steps: u32) -> BigUint {
     let (_initial, mut insertions) = parse_input(input);
 
-    for _ in 0..40 {
+    for _ in 0..steps {
         insertions = make_insertions_faster(&insertions);
         /*print_insertions(&insertions);
         let tail = &insertions
@@ -209,26 +208,72 @@ fn part_two(input: &str) -> u64 {
         dbg!(&insertions.values().map(|i| i.counter).sum::<u64>() + 1);*/
     }
 
-    let mut counts: HashMap<char, u64> = HashMap::new();
+    let mut counts: HashMap<char, BigUint> = HashMap::new();
     for insertion in insertions.values() {
-        *counts.entry(insertion.pair[0]).or_default() += insertion.counter;
+        *counts.entry(insertion.pair[0]).or_default() += &insertion.counter;
         if insertion.is_tail {
-            *counts.entry(insertion.pair[1]).or_default() += 1; //insertion.counter;
+            *counts.entry(insertion.pair[1]).or_default() += BigUint::one(); //insertion.counter;
         }
     }
     // dbg!(&counts);
 
-    let mut minc = u64::MAX;
-    let mut maxc = 0;
-    for ct in counts.values() {
-        minc = min(minc, *ct);
-        maxc = max(maxc, *ct);
-    }
+    let minc = counts.values().min().unwrap();
+    let maxc = counts.values().max().unwrap();
     maxc - minc
 }
 
+fn part_two(input: &str) -> BigUint {
+    polymerize(input, 40)
+}
+
 fn main() {
     let input = include_str!("day14.txt");
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
+
+    // `cargo run --bin day14 -- 500` to keep going for longer
+    if let Some(steps) = env::args().nth(1) {
+        let steps = steps.parse::<u32>().unwrap();
+        println!("STEP {}: {}", steps, polymerize(input, steps));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{part_one, part_two, polymerize};
+    use aoc2021::bigint::BigUint;
+    use aoc2021::text::variants;
+
+    const SAMPLE: &str = "NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C";
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), 1588);
+            assert_eq!(part_two(&input), BigUint::from(2188189693529u64));
+        }
+    }
+
+    #[test]
+    fn runs_past_u64() {
+        assert_eq!(polymerize(SAMPLE, 10), BigUint::from(1588u64));
+        assert!(polymerize(SAMPLE, 100).bits() > 64);
+    }
 }
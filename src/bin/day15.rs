@@ -1,3 +1,4 @@
+use aoc2021::text;
 use priority_queue::PriorityQueue;
 use std::collections::{HashMap, HashSet};
 
@@ -68,11 +69,16 @@ fn parse_input(input: &str, part_two: bool) -> Board {
     let mut cells: HashMap<u32, u8> = HashMap::new();
 
     let mut width: u32 = 0;
+    // Part two repeats the map in tiles as big as the input itself. This
+    // used to be a hard-coded 100, which only fit the real input and gave
+    // the wrong answer for the sample.
+    let mut tile: u32 = 0;
     let mut y: u32 = 0;
-    for line in input.split("\n") {
+    for line in text::lines(input) {
         let mut x: u32 = 0;
         if width == 0 {
             width = line.len() as u32;
+            tile = width;
             if part_two {
                 width *= 5;
             }
@@ -91,7 +97,10 @@ fn parse_input(input: &str, part_two: bool) -> Board {
                             continue;
                         }
                         let nlevel = ((level as u32 + (ym + xm) - 1) % 9) + 1;
-                        cells.insert(((y + (100 * ym)) * width) + (x + (xm * 100)), nlevel as u8);
+                        cells.insert(
+                            ((y + (tile * ym)) * width) + (x + (xm * tile)),
+                            nlevel as u8,
+                        );
                     }
                 }
             }
@@ -188,3 +197,28 @@ fn main() {
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{part_one, part_two};
+    use aoc2021::text::variants;
+
+    const SAMPLE: &str = "1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581";
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), 40);
+            assert_eq!(part_two(&input), 315);
+        }
+    }
+}
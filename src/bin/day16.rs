@@ -1,3 +1,4 @@
+use aoc2021::text;
 use bitreader::BitReader;
 use hex;
 use std::cmp::{max, min};
@@ -134,7 +135,7 @@ impl Packet {
 }
 
 fn parse_input(input: &str) -> Packet {
-    let hexed = hex::decode(input.as_bytes()).unwrap();
+    let hexed = hex::decode(text::trimmed(input).as_bytes()).unwrap();
     let mut bits = BitReader::new(&hexed);
     Packet::read(&mut bits)
 }
@@ -155,7 +156,8 @@ fn main() {
 
 #[cfg(test)]
 mod tests {
-    use crate::{parse_input, Packet};
+    use crate::{parse_input, part_one, part_two, Packet};
+    use aoc2021::text::variants;
 
     #[test]
     fn it_works() {
@@ -191,4 +193,21 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn handles_line_endings() {
+        for (sample, versions) in [
+            ("8A004A801A8002F478", 16),
+            ("620080001611562C8802118E34", 12),
+            ("C0015000016115A2E0802F182340", 23),
+            ("A0016C880162017C3686B18A3D4780", 31),
+        ] {
+            for input in variants(sample) {
+                assert_eq!(part_one(&input), versions);
+            }
+        }
+        for input in variants("04005AC33890") {
+            assert_eq!(part_two(&input), 54);
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use aoc2021::text;
 use regex::Regex;
 use std::cmp::max;
 
@@ -75,7 +76,7 @@ impl Probe {
 fn parse_input(input: &str) -> Bounds {
     let input_regex = Regex::new(r"^target area: x=(-?\d+)..(-?\d+), y=(-?\d+)..(-?\d+)$").unwrap();
 
-    let caps = input_regex.captures(input).unwrap();
+    let caps = input_regex.captures(text::trimmed(input)).unwrap();
 
     Bounds {
         tl: Point {
@@ -155,3 +156,17 @@ fn main() {
     // println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{part_one, part_two};
+    use aoc2021::text::variants;
+
+    #[test]
+    fn it_works() {
+        for input in variants("target area: x=20..30, y=-10..-5") {
+            assert_eq!(part_one(&input), 45);
+            assert_eq!(part_two(&input), 112);
+        }
+    }
+}
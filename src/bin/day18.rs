@@ -1,3 +1,4 @@
+use aoc2021::text;
 use std::cmp::max;
 
 #[derive(Debug, Clone)]
@@ -347,7 +348,7 @@ fn parse_pair(input: &[u8], pos: usize) -> (Pair, usize) {
 fn part_one(input: &str) -> u32 {
     let mut root = Pair::new();
 
-    for line in input.split("\n") {
+    for line in text::lines(input) {
         let (pair, _) = parse_pair(line.as_bytes(), 1);
         root = root.add(pair);
     }
@@ -356,7 +357,7 @@ fn part_one(input: &str) -> u32 {
 }
 
 fn part_two(input: &str) -> u32 {
-    let pairs: Vec<&[u8]> = input.split("\n").map(|l| l.as_bytes()).collect();
+    let pairs: Vec<&[u8]> = text::lines(input).iter().map(|l| l.as_bytes()).collect();
     let mut rv = 0;
 
     for test_idx in 0..pairs.len() {
@@ -380,3 +381,17 @@ fn main() {
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{part_one, part_two};
+    use aoc2021::text::variants;
+
+    #[test]
+    fn it_works() {
+        for input in variants("[1,1]\n[2,2]\n[3,3]\n[4,4]") {
+            assert_eq!(part_one(&input), 445);
+            assert_eq!(part_two(&input), 90);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use aoc2021::text;
 use std::cmp::max;
 use std::collections::{HashMap, HashSet};
 
@@ -243,11 +244,11 @@ impl Scanner {
     }
 }
 
-fn parse_input(input: &str) -> Vec<Scanner> {
+fn read_scanners(input: &str) -> Vec<Scanner> {
     let mut rv = Vec::new();
     let mut scanner = Scanner::new(0);
 
-    for line in input.split("\n") {
+    for line in text::lines(input) {
         if line.starts_with("---") {
             if scanner.beacons.len() > 0 {
                 scanner.calculate_distances();
@@ -267,6 +268,12 @@ fn parse_input(input: &str) -> Vec<Scanner> {
     scanner.calculate_distances();
     rv.push(scanner);
 
+    rv
+}
+
+fn parse_input(input: &str) -> Vec<Scanner> {
+    let mut rv = read_scanners(input);
+
     let mut mappings: HashMap<usize, HashSet<usize>> = HashMap::new();
 
     // Calculate the overlapping scanners and determine mappings
@@ -364,7 +371,8 @@ fn main() {
 
 #[cfg(test)]
 mod tests {
-    use crate::Beacon;
+    use crate::{read_scanners, Beacon};
+    use aoc2021::text::variants;
 
     #[test]
     fn it_works() {
@@ -389,4 +397,19 @@ mod tests {
         // Down
         assert_eq!(beacon.new_coords(5, 0), Beacon::new(10, 15, 5));
     }
+
+    #[test]
+    fn handles_line_endings() {
+        let beacons = |input: &str| -> Vec<usize> {
+            read_scanners(input)
+                .iter()
+                .map(|s| s.beacons.len())
+                .collect()
+        };
+        let expected = beacons(include_str!("day19.txt"));
+        assert_eq!(expected.len(), 37);
+        for input in variants(include_str!("day19.txt")) {
+            assert_eq!(beacons(&input), expected);
+        }
+    }
 }
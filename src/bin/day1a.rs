@@ -1,32 +1,34 @@
-fn do_something(input: &str) -> u32 {
-    let lines: Vec<u32> = input
-        .split("\n")
-        .map(|f| f.parse::<u32>().unwrap())
-        .collect();
+use aoc2021::sonar;
 
-    let mut incrs = 0;
-    let mut last_i = 0;
-    for i in lines {
-        if last_i != 0 && i > last_i {
-            incrs += 1;
-        }
-        last_i = i;
-    }
-    return incrs;
-}
+const WINDOW: usize = 1;
 
 fn main() {
-    let input = include_str!("day1.txt");
-    let answer = do_something(input);
-    println!("ANSWER: {}", answer)
+    sonar::run(include_str!("day1.txt"), WINDOW);
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
+    fn do_something(input: &str) -> u32 {
+        sonar::increases(input, WINDOW).unwrap().count as u32
+    }
+
     #[test]
     fn test_do_something() {
         assert_eq!(do_something(""), 0);
         assert_eq!(do_something(""), 0);
     }
+
+    #[test]
+    fn test_line_endings() {
+        for input in aoc2021::text::variants("199\n200\n208\n210\n200\n207\n240\n269\n260\n263") {
+            assert_eq!(do_something(&input), 7);
+        }
+    }
+
+    #[test]
+    fn test_zero_depth() {
+        assert_eq!(do_something("0\n1\n2\n3\n4"), 4);
+    }
 }
@@ -1,49 +1,285 @@
-fn part_one(input: &str) -> u32 {
-    let lines: Vec<&str> = input.split("\n").collect();
+use aoc2021::text;
+use std::env;
 
-    let mut horiz = 0;
-    let mut vert = 0;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Forward(i64),
+    Up(i64),
+    Down(i64),
+    Left(i64),
+    Right(i64),
+}
 
-    for line in lines {
-        let parts: Vec<&str> = line.split(" ").collect();
-        let num = parts[1].parse::<u32>().unwrap();
-        match parts[0] {
-            "forward" => horiz += num,
-            "up" => vert -= num,
-            "down" => vert += num,
-            _ => {}
+impl Command {
+    fn parse(line: &str) -> Result<Command, String> {
+        let (verb, num) = line
+            .split_once(' ')
+            .ok_or(format!("missing distance in {:?}", line))?;
+        let num = num
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| format!("bad distance in {:?}", line))?;
+        match verb {
+            "forward" => Ok(Command::Forward(num)),
+            "up" => Ok(Command::Up(num)),
+            "down" => Ok(Command::Down(num)),
+            "left" => Ok(Command::Left(num)),
+            "right" => Ok(Command::Right(num)),
+            _ => Err(format!("unknown command {:?}", verb)),
         }
     }
+}
 
-    return horiz * vert;
+fn parse_input(input: &str) -> Result<Vec<Command>, String> {
+    text::lines(input)
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| Command::parse(line).map_err(|e| format!("line {}: {}", idx + 1, e)))
+        .collect()
 }
 
-fn part_two(input: &str) -> u32 {
-    let lines: Vec<&str> = input.split("\n").collect();
+// Depth grows downwards, lateral grows to the right
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Position {
+    horiz: i64,
+    depth: i64,
+    lateral: i64,
+    aim: i64,
+}
 
-    let mut horiz = 0;
-    let mut vert = 0;
-    let mut aim = 0;
+trait NavigationModel {
+    // None if the model has no idea what to do with the command
+    fn apply(&self, position: Position, command: Command) -> Option<Position>;
+}
 
-    for line in lines {
-        let parts: Vec<&str> = line.split(" ").collect();
-        let num = parts[1].parse::<u32>().unwrap();
-        match parts[0] {
-            "forward" => {
-                horiz += num;
-                vert += aim * num;
+// Up and down change depth directly
+struct Plain;
+
+impl NavigationModel for Plain {
+    fn apply(&self, mut position: Position, command: Command) -> Option<Position> {
+        match command {
+            Command::Forward(num) => position.horiz += num,
+            Command::Up(num) => position.depth -= num,
+            Command::Down(num) => position.depth += num,
+            Command::Left(_) | Command::Right(_) => return None,
+        }
+        Some(position)
+    }
+}
+
+// Up and down tilt the submarine, forward then dives along the aim
+struct Aimed;
+
+impl NavigationModel for Aimed {
+    fn apply(&self, mut position: Position, command: Command) -> Option<Position> {
+        match command {
+            Command::Forward(num) => {
+                position.horiz += num;
+                position.depth += position.aim * num;
             }
-            "up" => aim -= num,
-            "down" => aim += num,
-            _ => {}
+            Command::Up(num) => position.aim -= num,
+            Command::Down(num) => position.aim += num,
+            Command::Left(_) | Command::Right(_) => return None,
         }
+        Some(position)
+    }
+}
+
+// Aimed, plus left and right strafing sideways without turning
+struct Strafing;
+
+impl NavigationModel for Strafing {
+    fn apply(&self, mut position: Position, command: Command) -> Option<Position> {
+        match command {
+            Command::Left(num) => position.lateral -= num,
+            Command::Right(num) => position.lateral += num,
+            _ => return Aimed.apply(position, command),
+        }
+        Some(position)
+    }
+}
+
+// Where the submarine is allowed to be. The puzzles never check, so by
+// default it's happy to fly above the surface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Limits {
+    surface: bool,
+    max_depth: Option<i64>,
+}
+
+impl Limits {
+    fn check(&self, position: &Position) -> Result<(), String> {
+        if self.surface && position.depth < 0 {
+            return Err(format!("above the surface at depth {}", position.depth));
+        }
+        match self.max_depth {
+            Some(max) if position.depth > max => Err(format!(
+                "below the maximum depth {} at depth {}",
+                max, position.depth
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Every position the submarine passes through, starting at the origin. Stops
+// at the first command the model can't follow or that breaks the limits.
+fn navigate<M: NavigationModel>(
+    model: &M,
+    commands: &[Command],
+    limits: &Limits,
+) -> Result<Vec<Position>, String> {
+    let mut rv = vec![Position::default()];
+    for (idx, command) in commands.iter().enumerate() {
+        let next = model.apply(*rv.last().unwrap(), *command).ok_or(format!(
+            "command {}: {:?} not supported",
+            idx + 1,
+            command
+        ))?;
+        limits
+            .check(&next)
+            .map_err(|e| format!("command {}: {:?} goes {}", idx + 1, command, e))?;
+        rv.push(next);
     }
+    Ok(rv)
+}
+
+fn final_product<M: NavigationModel>(model: &M, input: &str) -> i64 {
+    let commands = parse_input(input).unwrap();
+    let end = *navigate(model, &commands, &Limits::default())
+        .unwrap()
+        .last()
+        .unwrap();
+    end.horiz * end.depth
+}
 
-    return horiz * vert;
+fn part_one(input: &str) -> i64 {
+    final_product(&Plain, input)
+}
+
+fn part_two(input: &str) -> i64 {
+    final_product(&Aimed, input)
 }
 
 fn main() {
     let input = include_str!("day2.txt");
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
+
+    // `trajectory [plain|aimed|strafing] [max depth]` dumps every position
+    // along the way, keeping the submarine below the surface and optionally
+    // above a maximum depth
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|a| a.as_str()) == Some("trajectory") {
+        let commands = parse_input(input).unwrap();
+        let limits = Limits {
+            surface: true,
+            max_depth: args.get(3).map(|a| a.parse::<i64>().unwrap()),
+        };
+        let path = match args.get(2).map(|a| a.as_str()) {
+            Some("plain") => navigate(&Plain, &commands, &limits),
+            None | Some("aimed") => navigate(&Aimed, &commands, &limits),
+            Some("strafing") => navigate(&Strafing, &commands, &limits),
+            Some(other) => Err(format!("unknown model {:?}", other)),
+        };
+        match path {
+            Ok(path) => {
+                for (step, pos) in path.iter().enumerate() {
+                    println!(
+                        "{:4}: horiz {} depth {} lateral {} aim {}",
+                        step, pos.horiz, pos.depth, pos.lateral, pos.aim
+                    );
+                }
+            }
+            Err(e) => println!("STOPPED: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use aoc2021::text::variants;
+
+    #[test]
+    fn it_works() {
+        for input in variants("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2") {
+            assert_eq!(part_one(&input), 150);
+            assert_eq!(part_two(&input), 900);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_commands() {
+        assert_eq!(
+            parse_input("forward 5\nbackward 2"),
+            Err("line 2: unknown command \"backward\"".to_string())
+        );
+        assert!(parse_input("forward").is_err());
+        assert!(parse_input("up five").is_err());
+    }
+
+    #[test]
+    fn strafes_in_3d() {
+        let commands = parse_input("forward 2\ndown 1\nright 4\nforward 3\nleft 6").unwrap();
+        let path = navigate(&Strafing, &commands, &Limits::default()).unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(
+            path[3],
+            Position {
+                horiz: 2,
+                depth: 0,
+                lateral: 4,
+                aim: 1
+            }
+        );
+        assert_eq!(
+            path[5],
+            Position {
+                horiz: 5,
+                depth: 3,
+                lateral: -2,
+                aim: 1
+            }
+        );
+        assert_eq!(
+            navigate(&Plain, &commands, &Limits::default()),
+            Err("command 3: Right(4) not supported".to_string())
+        );
+    }
+
+    #[test]
+    fn checks_depth_limits() {
+        // Used to underflow a u32 and panic
+        let commands = parse_input("down 2\nup 5\nforward 1\ndown 9").unwrap();
+        let path = navigate(&Plain, &commands, &Limits::default()).unwrap();
+        assert_eq!(path[2].depth, -3);
+        assert_eq!(path[4].depth, 6);
+
+        let surface = Limits {
+            surface: true,
+            max_depth: None,
+        };
+        assert_eq!(
+            navigate(&Plain, &commands, &surface),
+            Err("command 2: Up(5) goes above the surface at depth -3".to_string())
+        );
+
+        let shallow = Limits {
+            surface: false,
+            max_depth: Some(5),
+        };
+        assert_eq!(
+            navigate(&Plain, &commands, &shallow),
+            Err("command 4: Down(9) goes below the maximum depth 5 at depth 6".to_string())
+        );
+
+        // Aiming up is fine as long as the submarine itself stays down
+        let commands = parse_input("forward 2\nup 3\nforward 1").unwrap();
+        assert_eq!(
+            navigate(&Aimed, &commands, &surface),
+            Err("command 3: Forward(1) goes above the surface at depth -3".to_string())
+        );
+        assert!(navigate(&Aimed, &commands[..2], &surface).is_ok());
+    }
 }
@@ -1,3 +1,4 @@
+use aoc2021::text;
 use std::cmp::{max, min};
 use std::collections::HashMap;
 
@@ -102,9 +103,10 @@ impl Image {
 fn parse_input(input: &str) -> Image {
     let mut rv = Image::new();
 
-    let mut lines = input.split("\n");
+    // The algorithm, a blank line, then the image
+    let sections = text::sections(input);
 
-    let algo = lines.next().unwrap();
+    let algo = sections[0].concat();
     rv.algo = Vec::from_iter(algo.split("").filter(|c| *c != "").map(|c| {
         if c == "#" {
             true
@@ -114,10 +116,8 @@ fn parse_input(input: &str) -> Image {
     }));
     assert_eq!(rv.algo.len(), 512);
 
-    let _ = lines.next();
-
     let mut y = 0;
-    for line in lines {
+    for line in &sections[1] {
         let mut x = 0;
         for chr in line.split("") {
             if chr == "" {
@@ -181,3 +181,16 @@ fn main() {
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::part_one;
+    use aoc2021::text::variants;
+
+    #[test]
+    fn it_works() {
+        for input in variants(include_str!("day20.txt")) {
+            assert_eq!(part_one(&input), 5339);
+        }
+    }
+}
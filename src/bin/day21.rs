@@ -1,5 +1,7 @@
+use aoc2021::bigint::BigUint;
 use std::cmp::{max, min};
 use std::collections::HashMap;
+use std::env;
 
 // Player 1 starting position: 10
 const START_P1: u8 = 10;
@@ -7,19 +9,26 @@ const START_P1: u8 = 10;
 // Player 2 starting position: 3
 const START_P2: u8 = 3;
 
+// Position and score for each player, then whose turn it is
+type State = (u8, u32, u8, u32, u8);
+
 struct World {
-    cache: HashMap<(u8, u8, u8, u8, u8), (u64, u64)>,
+    // Score needed to win, 21 in the puzzle
+    target: u32,
+    cache: HashMap<State, (BigUint, BigUint)>,
 }
 
 impl World {
-    fn add(&mut self, key: (u8, u8, u8, u8, u8), value: (u64, u64)) {
-        self.cache
+    fn add(&mut self, key: State, value: &(BigUint, BigUint)) {
+        let entry = self
+            .cache
             .entry(key)
-            .and_modify(|i| *i = (i.0 + value.0, i.1 + value.1))
-            .or_insert(value);
+            .or_insert((BigUint::zero(), BigUint::zero()));
+        entry.0 += &value.0;
+        entry.1 += &value.1;
     }
 
-    fn simulate(&mut self, key: (u8, u8, u8, u8, u8)) -> (u64, u64) {
+    fn simulate(&mut self, key: State) -> (BigUint, BigUint) {
         /*
         pos_p1: u8,
         score_p1: u8,
@@ -29,7 +38,7 @@ impl World {
 
         // If cached, return it
         if let Some(cached) = self.cache.get(&key) {
-            return *cached;
+            return cached.clone();
         }
 
         // Now let's try assuming that this player might role
@@ -39,39 +48,39 @@ impl World {
                     // Simulate player 1 if it's their turn
                     if key.4 == 1 {
                         let new_pos_p1 = ((((key.0 as u32 - 1) + (d1 + d2 + d3)) % 10) + 1) as u8;
-                        let new_score_p1 = key.1 + new_pos_p1;
-                        if new_score_p1 >= 21 {
+                        let new_score_p1 = key.1 + new_pos_p1 as u32;
+                        if new_score_p1 >= self.target {
                             // Player 1 wins in this state
-                            self.add(key, (1, 0));
+                            self.add(key, &(BigUint::one(), BigUint::zero()));
                         } else {
                             // No win, continue game from player 2's position and add that to
                             // our own winning cache
                             let new_key = (new_pos_p1, new_score_p1, key.2, key.3, 2);
                             let rv = self.simulate(new_key);
-                            self.add(key, rv);
+                            self.add(key, &rv);
                         }
                     }
 
                     // Simulate player 2
                     if key.4 == 2 {
                         let new_pos_p2 = ((((key.2 as u32 - 1) + (d1 + d2 + d3)) % 10) + 1) as u8;
-                        let new_score_p2 = key.3 + new_pos_p2;
-                        if new_score_p2 >= 21 {
+                        let new_score_p2 = key.3 + new_pos_p2 as u32;
+                        if new_score_p2 >= self.target {
                             // Player 1 wins in this state
-                            self.add(key, (0, 1));
+                            self.add(key, &(BigUint::zero(), BigUint::one()));
                         } else {
                             // No win, continue game from player 2's position and add that to
                             // our own winning cache
                             let new_key = (key.0, key.1, new_pos_p2, new_score_p2, 1);
                             let rv = self.simulate(new_key);
-                            self.add(key, rv);
+                            self.add(key, &rv);
                         }
                     }
                 }
             }
         }
 
-        let rv = *self.cache.get(&key).unwrap();
+        let rv = self.cache.get(&key).unwrap().clone();
         //dbg!(key, rv);
         return rv;
     }
@@ -105,19 +114,48 @@ fn part_one() -> u32 {
     rolls as u32 * min(score_p1, score_p2) as u32
 }
 
-fn part_two() -> u64 {
+// Universes won by whichever player wins in more of them
+fn count_universes(start_p1: u8, start_p2: u8, target: u32) -> BigUint {
     // Given starting positions, start by calculating who wins based on
     // each universe state, attempt to memoize??
 
     let mut world = World {
+        target,
         cache: HashMap::new(),
     };
 
-    let rv = world.simulate((START_P1, 0, START_P2, 0, 1));
+    let rv = world.simulate((start_p1, 0, start_p2, 0, 1));
     max(rv.0, rv.1)
 }
 
+fn part_two() -> BigUint {
+    count_universes(START_P1, START_P2, 21)
+}
+
 fn main() {
     println!("PART ONE: {}", part_one());
     println!("PART TWO: {}", part_two());
+
+    // `cargo run --bin day21 -- 50` to play to a higher score
+    if let Some(target) = env::args().nth(1) {
+        let target = target.parse::<u32>().unwrap();
+        println!(
+            "TARGET {}: {}",
+            target,
+            count_universes(START_P1, START_P2, target)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::count_universes;
+    use aoc2021::bigint::BigUint;
+
+    #[test]
+    fn it_works() {
+        // Example from the puzzle, players starting at 4 and 8
+        assert_eq!(count_universes(4, 8, 21), BigUint::from(444356092776315u64));
+        assert!(count_universes(4, 8, 30).bits() > 64);
+    }
 }
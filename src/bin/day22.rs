@@ -1,3 +1,4 @@
+use aoc2021::text;
 use std::cmp::{max, min};
 
 // I know this should be Cube, sorry
@@ -203,7 +204,7 @@ impl Rect {
 fn parse_input(input: &str, small_cube: bool) -> Vec<Rect> {
     let mut rv: Vec<Rect> = Vec::new();
 
-    for line in input.split("\n") {
+    for line in text::lines(input) {
         let rect = Rect::from_line(line);
 
         // Only include cubes that overlap our starting area
@@ -274,7 +275,8 @@ fn main() {
 
 #[cfg(test)]
 mod tests {
-    use crate::Rect;
+    use crate::{part_one, part_two, Rect};
+    use aoc2021::text::variants;
 
     #[test]
     fn it_works() {
@@ -288,4 +290,16 @@ mod tests {
         assert!(rect.overlaps(&Rect::new(-5, 18, -5, 18, -5, 18)));
         assert!(!rect.overlaps(&Rect::new(-5, 8, -5, -1, 5, 8)));
     }
+
+    #[test]
+    fn handles_line_endings() {
+        let sample = "on x=10..12,y=10..12,z=10..12
+on x=11..13,y=11..13,z=11..13
+off x=9..11,y=9..11,z=9..11
+on x=10..10,y=10..10,z=10..10";
+        for input in variants(sample) {
+            assert_eq!(part_one(&input), 39);
+            assert_eq!(part_two(&input), 39);
+        }
+    }
 }
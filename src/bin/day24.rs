@@ -1,3 +1,4 @@
+use aoc2021::text;
 use std::collections::HashMap;
 
 #[derive(Debug, PartialEq)]
@@ -66,7 +67,7 @@ fn parse_input(input: &str) -> Vec<Vec<Instruction>> {
     let mut rv = Vec::new();
 
     let mut temp_rv = Vec::new();
-    for line in input.split("\n") {
+    for line in text::lines(input) {
         let parts: Vec<&str> = line.split(" ").collect();
 
         let mut inst = Instruction::new();
@@ -197,3 +198,18 @@ fn main() {
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_input;
+    use aoc2021::text::variants;
+
+    #[test]
+    fn it_works() {
+        for input in variants(include_str!("day24.txt")) {
+            let sections = parse_input(&input);
+            assert_eq!(sections.len(), 14);
+            assert!(sections.iter().all(|s| s.len() == 18));
+        }
+    }
+}
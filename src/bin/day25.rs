@@ -1,3 +1,4 @@
+use aoc2021::text;
 use std::collections::HashMap;
 
 #[derive(Hash, Eq, PartialEq, Debug)]
@@ -23,7 +24,7 @@ fn parse_input(input: &str) -> World {
         points: HashMap::new(),
     };
 
-    for line in input.split("\n") {
+    for line in text::lines(input) {
         x = 0;
         for char in line.chars() {
             match char {
@@ -152,3 +153,26 @@ fn main() {
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::part_one;
+    use aoc2021::text::variants;
+
+    const SAMPLE: &str = "v...>>.vv>
+.vv>>.vv..
+>>.>v>...v
+>>v>>.>.v.
+v>v.vv.v..
+>.>>..v...
+.vv..>.>v.
+v.v..>>v.v
+....v..v.>";
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), 58);
+        }
+    }
+}
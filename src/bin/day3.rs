@@ -1,132 +1,259 @@
-fn part_one(input: &str) -> u32 {
-    let mut bits: Vec<Vec<usize>> = Vec::new();
-
-    for line in input.split("\n") {
-        let mut bit: Vec<usize> = Vec::new();
-        for num in line.split("") {
-            if num == "0" || num == "1" {
-                bit.push(num.parse::<usize>().unwrap());
-            }
-        }
-        bits.push(bit);
-    }
+use aoc2021::bigint::BigUint;
+use aoc2021::text;
+use std::env;
 
-    let cols = bits[0].len();
-    let rows = bits.len();
+// Every report line packed into an integer, most significant bit first as
+// written, so any width up to 128 bits fits
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Report {
+    width: u32,
+    values: Vec<u128>,
+}
 
-    let mut gamma = 0;
-    let mut epsilon = 0;
-    for col in 0..cols {
-        let mut count = 0;
-        for row in 0..rows {
-            count += bits[row][col];
-        }
+fn parse_input(input: &str) -> Result<Report, String> {
+    let lines = text::lines(input);
+    let width = lines.first().ok_or("empty report")?.len();
+    if width == 0 || width > 128 {
+        return Err(format!("reports must be 1 to 128 bits, not {}", width));
+    }
 
-        if count > (rows - count) {
-            gamma += 1 << (cols - col - 1);
-        } else {
-            epsilon += 1 << (cols - col - 1);
+    let mut values = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        if line.len() != width {
+            return Err(format!("line {}: expected {} bits", idx + 1, width));
+        }
+        if !line.chars().all(|c| c == '0' || c == '1') {
+            return Err(format!("line {}: not binary {:?}", idx + 1, line));
         }
+        values.push(u128::from_str_radix(line, 2).unwrap());
     }
 
-    gamma * epsilon
+    Ok(Report {
+        width: width as u32,
+        values,
+    })
 }
 
-fn filter_bits(inps: &Vec<Vec<usize>>, compare: &Vec<usize>) -> Vec<Vec<usize>> {
-    if compare.len() == 0 {
-        return inps.clone();
-    }
-
-    let mut rv: Vec<Vec<usize>> = Vec::new();
+// Which bit to keep when both values are equally common
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tie {
+    One,
+    Zero,
+    // Refuse to pick, the report doesn't have a single answer
+    Fail,
+}
 
-    for test in inps {
-        let mut keep = true;
-        for i in 0..compare.len() {
-            if test[i] != compare[i] {
-                keep = false;
-            }
-        }
-        if keep {
-            rv.push(test.clone());
+impl Tie {
+    fn bit(&self, position: u32) -> Result<u128, String> {
+        match self {
+            Tie::One => Ok(1),
+            Tie::Zero => Ok(0),
+            Tie::Fail => Err(format!("tied at bit {}", position)),
         }
     }
+}
 
-    rv
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Criteria {
+    MostCommon,
+    LeastCommon,
 }
 
-fn most_common_bit(inps: &Vec<Vec<usize>>, pos: usize, tie_return: usize) -> usize {
-    let mut count = 0;
-    let len = inps.len();
-    for row in 0..inps.len() {
-        count += inps[row][pos];
-    }
-    if count > (len - count) {
-        1
-    } else if count == (len - count) {
-        tie_return
-    } else {
-        0
+// Gamma takes the most common bit in each position and epsilon the other
+// one, `tie` decides gamma's bit when there are as many ones as zeros
+fn power_rates(report: &Report, tie: Tie) -> Result<(u128, u128), String> {
+    let mut gamma = 0;
+    let mut epsilon = 0;
+
+    for position in (0..report.width).rev() {
+        let ones = report
+            .values
+            .iter()
+            .filter(|v| *v >> position & 1 == 1)
+            .count();
+        let zeros = report.values.len() - ones;
+        let bit = if ones == zeros {
+            tie.bit(position)?
+        } else {
+            (ones > zeros) as u128
+        };
+        gamma |= bit << position;
+        epsilon |= (bit ^ 1) << position;
     }
+
+    Ok((gamma, epsilon))
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Node {
+    count: usize,
+    children: [Option<usize>; 2],
 }
 
-fn part_two(input: &str) -> u32 {
-    let mut bits: Vec<Vec<usize>> = Vec::new();
+// Binary trie of the report, most significant bit at the root. Each node
+// knows how many values sit below it, so the rating search is one walk down
+// instead of filtering the whole list again for every bit.
+#[derive(Debug)]
+struct Trie {
+    width: u32,
+    nodes: Vec<Node>,
+}
 
-    for line in input.split("\n") {
-        let mut bit: Vec<usize> = Vec::new();
-        for num in line.split("") {
-            if num == "0" || num == "1" {
-                bit.push(num.parse::<usize>().unwrap());
+impl Trie {
+    fn new(report: &Report) -> Trie {
+        let mut rv = Trie {
+            width: report.width,
+            nodes: vec![Node::default()],
+        };
+        for value in &report.values {
+            let mut node = 0;
+            rv.nodes[node].count += 1;
+            for position in (0..report.width).rev() {
+                let bit = (value >> position & 1) as usize;
+                node = match rv.nodes[node].children[bit] {
+                    Some(child) => child,
+                    None => {
+                        rv.nodes.push(Node::default());
+                        rv.nodes[node].children[bit] = Some(rv.nodes.len() - 1);
+                        rv.nodes.len() - 1
+                    }
+                };
+                rv.nodes[node].count += 1;
             }
         }
-        bits.push(bit);
+        rv
     }
 
-    let cols = bits[0].len();
-
-    let mut oxy = 0;
-    let mut co2 = 0;
-
-    // find oxygen
-    let mut filter: Vec<usize> = Vec::new();
-    let mut filtered = bits.clone();
-    for col in 0..cols {
-        let common_bit = most_common_bit(&filtered, col, 1);
-        filter.push(common_bit);
-        filtered = filter_bits(&bits, &filter);
-        if filtered.len() == 1 {
-            for col in 0..cols {
-                if filtered[0][col] == 1 {
-                    oxy += 1 << (cols - col - 1);
-                }
-            }
-            break;
-        }
+    fn count(&self, child: Option<usize>) -> usize {
+        child.map_or(0, |c| self.nodes[c].count)
     }
 
-    // find co2
-    let mut filter: Vec<usize> = Vec::new();
-    let mut filtered = bits.clone();
-    for col in 0..cols {
-        let common_bit = most_common_bit(&filtered, col, 1);
-        let least_common_bit = if common_bit == 1 { 0 } else { 1 };
-        filter.push(least_common_bit);
-        filtered = filter_bits(&bits, &filter);
-        if filtered.len() == 1 {
-            for col in 0..cols {
-                if filtered[0][col] == 1 {
-                    co2 += 1 << (cols - col - 1);
+    // Keeps narrowing down by bit criteria until one value is left. A bit
+    // nobody has is never kept, which is the same as stopping once a single
+    // value remains.
+    fn rating(&self, criteria: Criteria, tie: Tie) -> Result<u128, String> {
+        if self.nodes[0].count == 0 {
+            return Err("empty report".to_string());
+        }
+
+        let mut node = 0;
+        let mut rv = 0;
+        for position in (0..self.width).rev() {
+            let [zero, one] = self.nodes[node].children;
+            let (zeros, ones) = (self.count(zero), self.count(one));
+            let bit = if zeros == 0 {
+                1
+            } else if ones == 0 {
+                0
+            } else if zeros == ones {
+                tie.bit(position)?
+            } else {
+                match criteria {
+                    Criteria::MostCommon => (ones > zeros) as u128,
+                    Criteria::LeastCommon => (ones < zeros) as u128,
                 }
-            }
-            break;
+            };
+            rv |= bit << position;
+            node = self.nodes[node].children[bit as usize].unwrap();
         }
+
+        Ok(rv)
     }
+}
 
-    oxy * co2
+fn part_one(input: &str) -> BigUint {
+    let report = parse_input(input).unwrap();
+    let (gamma, epsilon) = power_rates(&report, Tie::Zero).unwrap();
+    BigUint::from(gamma) * BigUint::from(epsilon)
+}
+
+fn part_two(input: &str) -> BigUint {
+    let trie = Trie::new(&parse_input(input).unwrap());
+    let oxy = trie.rating(Criteria::MostCommon, Tie::One).unwrap();
+    let co2 = trie.rating(Criteria::LeastCommon, Tie::Zero).unwrap();
+    BigUint::from(oxy) * BigUint::from(co2)
 }
 
 fn main() {
     let input = include_str!("day3.txt");
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
+
+    // `strict` says whether the answers leaned on the tie rules at all
+    if env::args().nth(1).as_deref() == Some("strict") {
+        let report = parse_input(input).unwrap();
+        let trie = Trie::new(&report);
+        let checks = [
+            ("GAMMA", power_rates(&report, Tie::Fail).map(|r| r.0)),
+            ("OXYGEN", trie.rating(Criteria::MostCommon, Tie::Fail)),
+            ("CO2", trie.rating(Criteria::LeastCommon, Tie::Fail)),
+        ];
+        for (name, result) in checks {
+            match result {
+                Ok(value) => println!("{}: {} without ties", name, value),
+                Err(e) => println!("{}: {}", name, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use aoc2021::text::variants;
+
+    const SAMPLE: &str =
+        "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010";
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), BigUint::from(198u64));
+            assert_eq!(part_two(&input), BigUint::from(230u64));
+        }
+    }
+
+    #[test]
+    fn rates_with_ties() {
+        let trie = Trie::new(&parse_input(SAMPLE).unwrap());
+        assert_eq!(trie.rating(Criteria::MostCommon, Tie::One), Ok(23));
+        assert_eq!(trie.rating(Criteria::LeastCommon, Tie::Zero), Ok(10));
+        // Oxygen ties on its last bit, CO2 on its third
+        assert_eq!(trie.rating(Criteria::MostCommon, Tie::Zero), Ok(22));
+        assert_eq!(trie.rating(Criteria::LeastCommon, Tie::One), Ok(15));
+        assert_eq!(
+            trie.rating(Criteria::MostCommon, Tie::Fail),
+            Err("tied at bit 0".to_string())
+        );
+
+        let report = parse_input("10\n01").unwrap();
+        assert_eq!(power_rates(&report, Tie::One), Ok((3, 0)));
+        assert!(power_rates(&report, Tie::Fail).is_err());
+    }
+
+    #[test]
+    fn handles_wide_reports() {
+        let high = format!("1{}", "0".repeat(127));
+        let low = format!("0{}1", "1".repeat(126));
+        let input = format!("{}\n{}\n{}", high, high, low);
+        let report = parse_input(&input).unwrap();
+        assert_eq!(report.width, 128);
+        assert_eq!(
+            power_rates(&report, Tie::Zero),
+            Ok((1 << 127, u128::MAX >> 1))
+        );
+
+        let trie = Trie::new(&report);
+        assert_eq!(trie.rating(Criteria::MostCommon, Tie::One), Ok(1 << 127));
+        assert_eq!(
+            trie.rating(Criteria::LeastCommon, Tie::Zero),
+            Ok(u128::MAX >> 1)
+        );
+        assert_eq!(part_one(&input).bits(), 254);
+
+        assert!(parse_input(&format!("{}0", high)).is_err());
+        assert!(parse_input("101\n11").is_err());
+        assert!(parse_input("102").is_err());
+        assert!(parse_input("+10").is_err());
+    }
 }
@@ -1,134 +1,320 @@
-use std::collections::HashSet;
+use aoc2021::rng::Rng;
+use aoc2021::text;
+use std::env;
 
-#[derive(Debug)]
+// An N by N board, numbers stored row by row
+#[derive(Debug, Clone)]
 struct Board {
-    won: bool,
-    rows: Vec<HashSet<u32>>,
-    cols: Vec<HashSet<u32>>,
+    size: usize,
+    cells: Vec<u32>,
 }
 
-fn board_score(board: &Board) -> u32 {
-    let mut result = 0;
-    for idx in 0..5 {
-        for num in &board.rows[idx] {
-            result += num;
+impl Board {
+    fn from_rows(rows: &[&str]) -> Board {
+        let mut cells = Vec::new();
+        for row in rows {
+            cells.extend(row.split_whitespace().map(|n| n.parse::<u32>().unwrap()));
         }
+        let size = rows.len();
+        assert_eq!(cells.len(), size * size, "board isn't square: {:?}", rows);
+        Board { size, cells }
     }
 
-    result
-}
-
-fn new_board() -> Board {
-    Board {
-        won: false,
-        rows: Vec::new(),
-        cols: vec![HashSet::new(); 5],
+    // Every line that can win, as the cells it's made of
+    fn lines(&self, diagonals: bool) -> Vec<(Line, Vec<usize>)> {
+        let n = self.size;
+        let mut rv = Vec::new();
+        for idx in 0..n {
+            rv.push((Line::Row(idx), (0..n).map(|col| idx * n + col).collect()));
+        }
+        for idx in 0..n {
+            rv.push((Line::Column(idx), (0..n).map(|row| row * n + idx).collect()));
+        }
+        if diagonals {
+            rv.push((Line::Diagonal, (0..n).map(|i| i * n + i).collect()));
+            rv.push((
+                Line::AntiDiagonal,
+                (0..n).map(|i| i * n + n - 1 - i).collect(),
+            ));
+        }
+        rv
     }
 }
 
 fn parse_input(input: &str) -> (Vec<u32>, Vec<Board>) {
-    let mut numbers: Vec<u32> = Vec::new();
-    let mut boards: Vec<Board> = Vec::new();
-
-    // Parse the input into boards
-    let mut idx = 0;
-    let mut board = new_board();
-    for line in input.split("\n") {
-        if idx == 0 {
-            numbers = line.split(",").map(|n| n.parse::<u32>().unwrap()).collect();
-        } else if line == "" {
-            if board.rows.len() == 5 {
-                boards.push(board);
-            }
-            board = new_board();
-        } else {
-            let row: Vec<u32> = line
-                .split(" ")
-                .filter(|l| *l != "")
-                .map(|n| n.parse::<u32>().unwrap())
-                .collect();
-            for idx in 0..5 {
-                board.cols[idx].insert(row[idx]);
-            }
-            let mut rownums: HashSet<u32> = HashSet::new();
-            for num in row {
-                rownums.insert(num);
-            }
-            board.rows.push(rownums);
-        }
-        idx += 1;
-    }
-    if board.rows.len() == 5 {
-        boards.push(board);
-    }
+    let sections = text::sections(input);
+
+    // First section is the draw order, every one after that is a board
+    let numbers: Vec<u32> = text::fields(sections[0][0], ',')
+        .iter()
+        .map(|n| n.parse::<u32>().unwrap())
+        .collect();
+    let boards = sections[1..].iter().map(|s| Board::from_rows(s)).collect();
 
     (numbers, boards)
 }
 
-fn part_one(input: &str) -> u32 {
-    let (numbers, mut boards) = parse_input(input);
-
-    // Iterate the numbers removing from everything
-    for num in numbers {
-        for idx in 0..boards.len() {
-            for rowcol in 0..5 {
-                if boards[idx].rows[rowcol].remove(&num) {
-                    if boards[idx].rows[rowcol].len() == 0 {
-                        boards[idx].won = true;
-                    }
-                }
-                if boards[idx].cols[rowcol].remove(&num) {
-                    if boards[idx].cols[rowcol].len() == 0 {
-                        boards[idx].won = true;
-                    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line {
+    Row(usize),
+    Column(usize),
+    // Top left to bottom right
+    Diagonal,
+    AntiDiagonal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Win {
+    board: usize,
+    // How many numbers had been drawn, counting the winning one
+    turn: usize,
+    number: u32,
+    line: Line,
+    score: u32,
+}
+
+// Plays the whole draw and returns the wins in the order they happened,
+// boards winning on the same number in board order. Boards that never win
+// aren't in there.
+fn play(numbers: &[u32], boards: &[Board], diagonals: bool) -> Vec<Win> {
+    let mut marked: Vec<Vec<bool>> = boards.iter().map(|b| vec![false; b.cells.len()]).collect();
+    let lines: Vec<_> = boards.iter().map(|b| b.lines(diagonals)).collect();
+    let mut won = vec![false; boards.len()];
+    let mut rv = Vec::new();
+
+    for (turn, num) in numbers.iter().enumerate() {
+        for (idx, board) in boards.iter().enumerate() {
+            if won[idx] {
+                continue;
+            }
+            let mut hit = false;
+            for (cell, value) in board.cells.iter().enumerate() {
+                if value == num {
+                    marked[idx][cell] = true;
+                    hit = true;
                 }
             }
-            if boards[idx].won {
-                return board_score(&boards[idx]) * num;
+            if !hit {
+                continue;
+            }
+
+            let done = lines[idx]
+                .iter()
+                .find(|(_, cells)| cells.iter().all(|c| marked[idx][*c]));
+            if let Some((line, _)) = done {
+                won[idx] = true;
+                let unmarked: u32 = board
+                    .cells
+                    .iter()
+                    .zip(&marked[idx])
+                    .filter(|(_, m)| !**m)
+                    .map(|(v, _)| v)
+                    .sum();
+                rv.push(Win {
+                    board: idx,
+                    turn: turn + 1,
+                    number: *num,
+                    line: *line,
+                    score: unmarked * num,
+                });
             }
         }
     }
 
-    0
+    rv
 }
 
-fn part_two(input: &str) -> u32 {
-    let (numbers, mut boards) = parse_input(input);
+// How one board fared over many shuffled draws
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct BoardStats {
+    // Trials where it was the first (or last) to win, ties count for everyone
+    // who won on that turn
+    first: usize,
+    last: usize,
+    wins: usize,
+    turns: usize,
+    fastest: Option<usize>,
+}
 
-    let mut boards_won = 0;
+impl BoardStats {
+    fn mean_turn(&self) -> Option<f64> {
+        (self.wins > 0).then(|| self.turns as f64 / self.wins as f64)
+    }
+}
 
-    // Iterate the numbers removing from everything
-    for num in numbers {
-        for idx in 0..boards.len() {
-            if boards[idx].won {
-                continue;
-            }
-            for rowcol in 0..5 {
-                if boards[idx].rows[rowcol].remove(&num) {
-                    if boards[idx].rows[rowcol].len() == 0 {
-                        boards[idx].won = true;
-                    }
-                }
-                if boards[idx].cols[rowcol].remove(&num) {
-                    if boards[idx].cols[rowcol].len() == 0 {
-                        boards[idx].won = true;
-                    }
-                }
+// Monte-Carlo over draw orders: shuffles the draws `trials` times with a
+// seeded RNG and plays every game out, so the same seed always gives the
+// same numbers
+fn simulate(
+    numbers: &[u32],
+    boards: &[Board],
+    diagonals: bool,
+    trials: usize,
+    seed: u64,
+) -> Vec<BoardStats> {
+    let mut rng = Rng::new(seed);
+    let mut draws = numbers.to_vec();
+    let mut rv = vec![BoardStats::default(); boards.len()];
+
+    for _ in 0..trials {
+        rng.shuffle(&mut draws);
+        let wins = play(&draws, boards, diagonals);
+        let (Some(first), Some(last)) = (wins.first(), wins.last()) else {
+            continue;
+        };
+        for win in &wins {
+            let stats = &mut rv[win.board];
+            stats.wins += 1;
+            stats.turns += win.turn;
+            stats.fastest = Some(stats.fastest.map_or(win.turn, |f| f.min(win.turn)));
+            if win.turn == first.turn {
+                stats.first += 1;
             }
-            if boards[idx].won {
-                boards_won += 1;
-                if boards_won == boards.len() {
-                    return board_score(&boards[idx]) * num;
-                }
+            if win.turn == last.turn {
+                stats.last += 1;
             }
         }
     }
 
-    0
+    rv
+}
+
+fn part_one(input: &str) -> u32 {
+    let (numbers, boards) = parse_input(input);
+    play(&numbers, &boards, false)
+        .first()
+        .map_or(0, |w| w.score)
+}
+
+fn part_two(input: &str) -> u32 {
+    let (numbers, boards) = parse_input(input);
+    play(&numbers, &boards, false).last().map_or(0, |w| w.score)
 }
 
 fn main() {
     let input = include_str!("day4.txt");
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
+
+    // `wins [diagonals]` lists every board in the order it won
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|a| a.as_str()) == Some("wins") {
+        let (numbers, boards) = parse_input(input);
+        let diagonals = args.get(2).map(|a| a.as_str()) == Some("diagonals");
+        for win in play(&numbers, &boards, diagonals) {
+            println!(
+                "TURN {}: board {} on {} with {:?}, score {}",
+                win.turn, win.board, win.number, win.line, win.score
+            );
+        }
+    }
+
+    // `simulate <trials> [seed]` shuffles the draws and ranks the boards by
+    // how often they won first
+    if args.get(1).map(|a| a.as_str()) == Some("simulate") {
+        let (numbers, boards) = parse_input(input);
+        let trials = args.get(2).map_or(1000, |a| a.parse::<usize>().unwrap());
+        let seed = args.get(3).map_or(2021, |a| a.parse::<u64>().unwrap());
+        let stats = simulate(&numbers, &boards, false, trials, seed);
+
+        let mut order: Vec<usize> = (0..boards.len()).collect();
+        order.sort_by_key(|b| std::cmp::Reverse(stats[*b].first));
+        for board in order {
+            let s = &stats[board];
+            println!(
+                "BOARD {}: first {:.3} last {:.3} mean turn {:.1} fastest {}",
+                board,
+                s.first as f64 / trials as f64,
+                s.last as f64 / trials as f64,
+                s.mean_turn().unwrap_or(f64::NAN),
+                s.fastest.map_or("never".to_string(), |f| f.to_string())
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use aoc2021::text::variants;
+
+    const SAMPLE: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7";
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), 4512);
+            assert_eq!(part_two(&input), 1924);
+        }
+    }
+
+    #[test]
+    fn orders_every_win() {
+        let (numbers, boards) = parse_input(SAMPLE);
+        let wins = play(&numbers, &boards, false);
+        assert_eq!(
+            wins.iter()
+                .map(|w| (w.board, w.turn, w.number))
+                .collect::<Vec<_>>(),
+            vec![(2, 12, 24), (0, 14, 16), (1, 15, 13)]
+        );
+        assert_eq!(wins[0].line, Line::Row(0));
+        assert_eq!(wins[0].score, 4512);
+        assert_eq!(wins[2].score, 1924);
+    }
+
+    #[test]
+    fn plays_other_sizes() {
+        let input = "5,1,9,3,7\n\n1 2 3\n4 5 6\n7 8 9\n\n9 2\n4 6";
+        let (numbers, boards) = parse_input(input);
+        assert_eq!(boards[0].size, 3);
+        assert_eq!(boards[1].size, 2);
+        assert!(play(&numbers, &boards, false).is_empty());
+
+        let wins = play(&numbers, &boards, true);
+        assert_eq!(wins.len(), 1);
+        assert_eq!(wins[0].line, Line::Diagonal);
+        assert_eq!((wins[0].turn, wins[0].number), (3, 9));
+        assert_eq!(wins[0].score, (2 + 3 + 4 + 6 + 7 + 8) * 9);
+    }
+
+    #[test]
+    fn simulates_shuffled_draws() {
+        let (numbers, boards) = parse_input(SAMPLE);
+        let stats = simulate(&numbers, &boards, false, 200, 7);
+        assert_eq!(stats, simulate(&numbers, &boards, false, 200, 7));
+
+        // All 27 numbers get drawn, so every board wins every time
+        for s in &stats {
+            assert_eq!(s.wins, 200);
+            assert!(s.fastest.unwrap() >= 5);
+            assert!(s.mean_turn().unwrap() <= 27.0);
+        }
+        assert!(stats.iter().map(|s| s.first).sum::<usize>() >= 200);
+        assert!(stats.iter().map(|s| s.last).sum::<usize>() >= 200);
+
+        // A board that can't win never shows up
+        let (numbers, boards) = parse_input("1,2\n\n1 2\n3 4\n\n5 6\n7 8");
+        let stats = simulate(&numbers, &boards, false, 10, 1);
+        assert_eq!(stats[0].first, 10);
+        assert_eq!(stats[1], BoardStats::default());
+    }
 }
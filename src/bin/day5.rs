@@ -1,23 +1,24 @@
-use core::cmp::max;
+use aoc2021::text;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::env;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 struct Point {
-    x: i32,
-    y: i32,
+    x: i64,
+    y: i64,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Line {
     start: Point,
     end: Point,
 }
 
-type Board = Vec<Vec<i32>>;
-
 fn parse_point(input: &str) -> Point {
-    let nums: Vec<i32> = input
+    let nums: Vec<i64> = input
         .split(",")
-        .map(|n| n.parse::<i32>().unwrap())
+        .map(|n| n.parse::<i64>().unwrap())
         .collect();
     return Point {
         x: nums[0],
@@ -27,7 +28,7 @@ fn parse_point(input: &str) -> Point {
 
 fn parse_lines(input: &str) -> Vec<Line> {
     let mut rv = Vec::new();
-    for line in input.split("\n") {
+    for line in text::lines(input) {
         let parts: Vec<&str> = line.split(" ").collect();
         let point1 = parse_point(&parts[0]);
         let point2 = parse_point(&parts[2]);
@@ -39,79 +40,364 @@ fn parse_lines(input: &str) -> Vec<Line> {
     rv
 }
 
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// a * b as a sign and a size. Any difference of two i64s fits in 65 bits,
+// so the product of two of them always fits a u128 even when it's too big
+// for an i128.
+fn product(a: i128, b: i128) -> (i128, u128) {
+    (a.signum() * b.signum(), a.unsigned_abs() * b.unsigned_abs())
+}
+
+// Which way v turns from d: the sign of their cross product, worked out
+// without ever forming it
+fn turn(d: (i128, i128), v: (i128, i128)) -> Ordering {
+    let (left, right) = (product(d.0, v.1), product(d.1, v.0));
+    match left.0.cmp(&right.0) {
+        Ordering::Equal if left.0 < 0 => right.1.cmp(&left.1),
+        Ordering::Equal => left.1.cmp(&right.1),
+        other => other,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Intersection {
+    None,
+    Point(Point),
+    // The lines cross, but between grid points
+    OffGrid,
+    // Collinear and sharing this stretch
+    Overlap(Line),
+}
+
 impl Line {
-    fn plot(self: &Line, board: &mut Board, include_diagonals: bool) {
-        let dx = self.end.x - self.start.x;
-        let dy = self.end.y - self.start.y;
+    fn is_diagonal(&self) -> bool {
+        self.start.x != self.end.x && self.start.y != self.end.y
+    }
 
-        let mut slope_x = 0;
-        let mut slope_y = 0;
+    // Every grid point on the line, walking from start to end in steps of
+    // (dx, dy) / gcd(dx, dy) so any slope lands exactly on the lattice. The
+    // deltas are i128 since two i64 ends can be more than i64::MAX apart.
+    // There's one per lattice point, so a line billions of points long takes
+    // billions of steps to walk; overlaps never does.
+    fn points(&self, with_endpoints: bool) -> impl Iterator<Item = Point> {
+        let start = (self.start.x as i128, self.start.y as i128);
+        let dx = self.end.x as i128 - start.0;
+        let dy = self.end.y as i128 - start.1;
+        // A single point has one step of nothing
+        let steps = gcd(dx, dy).max(1);
+        let (first, last) = match (with_endpoints, dx == 0 && dy == 0) {
+            (true, true) => (0, 0),
+            (true, false) => (0, steps),
+            (false, _) => (1, steps - 1),
+        };
+        (first..=last).map(move |i| Point {
+            x: (start.0 + dx / steps * i) as i64,
+            y: (start.1 + dy / steps * i) as i64,
+        })
+    }
 
-        if dx != 0 && dy == 0 {
-            slope_x = dx / dx.abs();
-        } else if dx == 0 && dy != 0 {
-            slope_y = dy / dy.abs();
+    // The grid points on a line are start + k * step for k from 0 to the
+    // gcd, so this finds k from whichever axis moves. Nothing gets
+    // multiplied past the size of the line, even across the whole i64 range.
+    fn contains(&self, point: &Point) -> bool {
+        let d = self.delta();
+        let p = (
+            point.x as i128 - self.start.x as i128,
+            point.y as i128 - self.start.y as i128,
+        );
+        let steps = gcd(d.0, d.1);
+        if steps == 0 {
+            return p == (0, 0);
+        }
+        let step = (d.0 / steps, d.1 / steps);
+        let (s_along, s_across, p_along, p_across) = if step.0 != 0 {
+            (step.0, step.1, p.0, p.1)
         } else {
-            if include_diagonals {
-                slope_x = dx / dx.abs();
-                slope_y = dy / dy.abs();
-            } else {
-                return;
-            }
+            (step.1, step.0, p.1, p.0)
+        };
+        if p_along % s_along != 0 {
+            return false;
         }
+        let k = p_along / s_along;
+        (0..=steps).contains(&k) && p_across == k * s_across
+    }
+
+    fn delta(&self) -> (i128, i128) {
+        (
+            self.end.x as i128 - self.start.x as i128,
+            self.end.y as i128 - self.start.y as i128,
+        )
+    }
 
-        let mut cur_x = self.start.x;
-        let mut cur_y = self.start.y;
+    // Which side of the line the point is on, Equal for anywhere along it
+    // (past the ends too)
+    fn side(&self, point: &Point) -> Ordering {
+        turn(
+            self.delta(),
+            (
+                point.x as i128 - self.start.x as i128,
+                point.y as i128 - self.start.y as i128,
+            ),
+        )
+    }
+
+    // The k-th grid point from the start, for k up to the gcd
+    fn at(&self, k: i128) -> Point {
+        let d = self.delta();
+        let steps = gcd(d.0, d.1).max(1);
+        Point {
+            x: (self.start.x as i128 + d.0 / steps * k) as i64,
+            y: (self.start.y as i128 + d.1 / steps * k) as i64,
+        }
+    }
+
+    // The same line less its two ends, None if that leaves nothing
+    fn inside(&self) -> Option<Line> {
+        let d = self.delta();
+        let steps = gcd(d.0, d.1);
+        if steps < 2 {
+            return None;
+        }
+        Some(Line {
+            start: self.at(1),
+            end: self.at(steps - 1),
+        })
+    }
 
-        loop {
-            board[cur_y as usize][cur_x as usize] += 1;
+    // How many grid steps a point somewhere on the line (past the ends too)
+    // is from the start, going backwards if it's behind
+    fn offset(&self, point: &Point) -> i128 {
+        let d = self.delta();
+        let steps = gcd(d.0, d.1);
+        if d.0 != 0 {
+            (point.x as i128 - self.start.x as i128) / (d.0 / steps)
+        } else {
+            (point.y as i128 - self.start.y as i128) / (d.1 / steps)
+        }
+    }
 
-            if cur_x == self.end.x && cur_y == self.end.y {
-                break;
+    // Where two segments meet. The cross products themselves can need ~130
+    // bits, so this only ever asks for their signs, and finds the crossing by
+    // bisecting the grid points along self.
+    fn intersection(&self, other: &Line) -> Intersection {
+        if turn(self.delta(), other.delta()) == Ordering::Equal {
+            // Parallel, so they can only share a stretch of the same line.
+            // Its ends are whichever endpoints lie on both segments, in the
+            // order self runs.
+            let mut shared: Vec<Point> = [self.start, self.end, other.start, other.end]
+                .into_iter()
+                .filter(|pt| self.contains(pt) && other.contains(pt))
+                .collect();
+            shared.sort();
+            let dir = if self.start != self.end {
+                self.delta()
+            } else {
+                other.delta()
+            };
+            if dir.0 < 0 || (dir.0 == 0 && dir.1 < 0) {
+                shared.reverse();
             }
+            return match (shared.first(), shared.last()) {
+                (Some(a), Some(b)) if a == b => Intersection::Point(*a),
+                (Some(a), Some(b)) => Intersection::Overlap(Line { start: *a, end: *b }),
+                _ => Intersection::None,
+            };
+        }
 
-            cur_x += slope_x;
-            cur_y += slope_y;
+        // Both ends of one on the same side of the other means no crossing
+        let apart = |a: &Line, b: &Line| {
+            let side = a.side(&b.start);
+            side != Ordering::Equal && side == a.side(&b.end)
+        };
+        if apart(self, other) || apart(other, self) {
+            return Intersection::None;
         }
+
+        // The crossing is somewhere on self, so look for a grid point of
+        // self that's right on other. Sides only flip once along the way.
+        let d = self.delta();
+        let (mut lo, mut hi) = (0, gcd(d.0, d.1));
+        let before = other.side(&self.at(lo));
+        for k in [lo, hi] {
+            if other.side(&self.at(k)) == Ordering::Equal {
+                return Intersection::Point(self.at(k));
+            }
+        }
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            match other.side(&self.at(mid)) {
+                Ordering::Equal => return Intersection::Point(self.at(mid)),
+                side if side == before => lo = mid,
+                _ => hi = mid,
+            }
+        }
+        Intersection::OffGrid
     }
 }
 
-fn print_board(board: &Board) {
-    for y in 0..board.len() {
-        for x in 0..board[y as usize].len() {
-            print!("{}", board[y as usize][x as usize]);
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct Overlaps {
+    // Grid points with at least two lines through them
+    count: u128,
+    // Most lines through any one point
+    max_depth: u32,
+    // Where lines cross, outside of the stretches. Sorted by x then y.
+    points: Vec<Point>,
+    // Where lines run along each other, merged and sorted by their ends
+    stretches: Vec<Line>,
+}
+
+impl Overlaps {
+    // Every one of the points, which can be a lot of them
+    fn all_points(&self) -> Vec<Point> {
+        let mut rv = self.points.clone();
+        for stretch in &self.stretches {
+            rv.extend(stretch.points(true));
         }
-        println!("");
+        rv.sort();
+        rv
     }
 }
 
-fn part(input: &str, with_diagonals: bool) -> u32 {
-    let lines = parse_lines(input);
+// Lines at any angle count as diagonals, and leaving out the endpoints only
+// counts vents crossing somewhere along their length. Every pair of lines
+// meets at one point or along one stretch, so this works from those and
+// never walks the lines themselves.
+fn overlaps(lines: &[Line], with_diagonals: bool, with_endpoints: bool) -> Overlaps {
+    let lines: Vec<Line> = lines
+        .iter()
+        .filter(|line| with_diagonals || !line.is_diagonal())
+        .filter_map(|line| {
+            if with_endpoints {
+                Some(*line)
+            } else {
+                line.inside()
+            }
+        })
+        .collect();
 
-    let mut max_x = 0;
-    let mut max_y = 0;
-    for idx in 0..lines.len() {
-        max_x = max(max_x, max(lines[idx].start.x, lines[idx].end.x));
-        max_y = max(max_y, max(lines[idx].start.y, lines[idx].end.y));
+    // The lines through each crossing, and every stretch two lines share
+    let mut crossings: HashMap<Point, HashSet<usize>> = HashMap::new();
+    let mut shared = Vec::new();
+    for (i, a) in lines.iter().enumerate() {
+        for (j, b) in lines.iter().enumerate().skip(i + 1) {
+            match a.intersection(b) {
+                Intersection::Point(point) => crossings.entry(point).or_default().extend([i, j]),
+                Intersection::Overlap(stretch) => shared.push(stretch),
+                _ => {}
+            }
+        }
     }
 
-    let mut board: Board = vec![vec![0; (max_x + 1) as usize]; (max_y + 1) as usize];
-    for idx in 0..lines.len() {
-        lines[idx].plot(&mut board, with_diagonals);
+    // Stretches on the same infinite line get merged, as spans of grid steps
+    // along whichever of them came first
+    let mut runs: Vec<(Line, Vec<(i128, i128)>)> = Vec::new();
+    for stretch in shared {
+        let idx = match runs.iter().position(|(along, _)| {
+            along.side(&stretch.start) == Ordering::Equal
+                && along.side(&stretch.end) == Ordering::Equal
+        }) {
+            Some(idx) => idx,
+            None => {
+                runs.push((stretch, Vec::new()));
+                runs.len() - 1
+            }
+        };
+        let (along, spans) = &mut runs[idx];
+        let (a, b) = (along.offset(&stretch.start), along.offset(&stretch.end));
+        spans.push((a.min(b), a.max(b)));
     }
+    let mut stretches = Vec::new();
+    for (along, spans) in &mut runs {
+        spans.sort();
+        let mut merged: Vec<(i128, i128)> = Vec::new();
+        for &(lo, hi) in spans.iter() {
+            match merged.last_mut() {
+                Some(last) if lo <= last.1 + 1 => last.1 = last.1.max(hi),
+                _ => merged.push((lo, hi)),
+            }
+        }
+        *spans = merged;
+        for &(lo, hi) in spans.iter() {
+            let (start, end) = (along.at(lo), along.at(hi));
+            stretches.push(Line {
+                start: start.min(end),
+                end: start.max(end),
+            });
+        }
+    }
+    stretches.sort_by_key(|stretch| (stretch.start, stretch.end));
+    let on_stretch = |point: &Point| {
+        runs.iter().any(|(along, spans)| {
+            along.side(point) == Ordering::Equal && {
+                let k = along.offset(point);
+                spans.iter().any(|(lo, hi)| (lo..=hi).contains(&&k))
+            }
+        })
+    };
 
-    // print_board(&board);
-
-    let mut count = 0;
-    for y in 0..max_y + 1 {
-        for x in 0..max_x + 1 {
-            if board[y as usize][x as usize] >= 2 {
-                count += 1;
+    let mut count: u128 = runs
+        .iter()
+        .flat_map(|(_, spans)| spans)
+        .map(|(lo, hi)| (hi - lo + 1) as u128)
+        .sum();
+    // Stretches on different lines can still cross, and those points got
+    // counted once for each
+    let mut doubled = HashSet::new();
+    for (i, a) in stretches.iter().enumerate() {
+        for b in &stretches[i + 1..] {
+            if let Intersection::Point(point) = a.intersection(b) {
+                doubled.insert(point);
             }
         }
     }
+    for point in &doubled {
+        count -= stretches.iter().filter(|s| s.contains(point)).count() as u128 - 1;
+    }
 
-    count
+    let mut points: Vec<Point> = crossings
+        .keys()
+        .filter(|point| !on_stretch(point))
+        .copied()
+        .collect();
+    points.sort();
+    count += points.len() as u128;
+
+    // Off the stretches, every line through a crossing crosses all the
+    // others there. Along them the depth only changes at the end of a line or
+    // where another one crosses, so those are the only places to count.
+    let depth = |point: &Point| lines.iter().filter(|line| line.contains(point)).count();
+    let max_depth = points
+        .iter()
+        .map(|point| crossings[point].len())
+        .chain(
+            crossings
+                .keys()
+                .chain(lines.iter().flat_map(|line| [&line.start, &line.end]))
+                .filter(|point| on_stretch(point))
+                .map(depth),
+        )
+        .max()
+        .unwrap_or(0)
+        .max(lines.len().min(1));
+
+    Overlaps {
+        count,
+        max_depth: max_depth as u32,
+        points,
+        stretches,
+    }
+}
+
+fn part(input: &str, with_diagonals: bool) -> u32 {
+    overlaps(&parse_lines(input), with_diagonals, true).count as u32
 }
 
 fn part_one(input: &str) -> u32 {
@@ -126,4 +412,332 @@ fn main() {
     let input = include_str!("day5.txt");
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
+
+    // `overlaps [inside]` lists every point where vents cross, `inside`
+    // ignoring the ends of each vent. `intersect <a> <b>` says where two of
+    // the vents (counting from 1) meet.
+    let args: Vec<String> = env::args().collect();
+    let lines = parse_lines(input);
+    match args.get(1).map(|a| a.as_str()) {
+        Some("overlaps") => {
+            let with_endpoints = args.get(2).map(|a| a.as_str()) != Some("inside");
+            let found = overlaps(&lines, true, with_endpoints);
+            println!("DEEPEST: {} lines", found.max_depth);
+            for point in found.all_points() {
+                println!("{},{}", point.x, point.y);
+            }
+        }
+        Some("intersect") => {
+            let a = args[2].parse::<usize>().unwrap();
+            let b = args[3].parse::<usize>().unwrap();
+            println!(
+                "INTERSECTION: {:?}",
+                lines[a - 1].intersection(&lines[b - 1])
+            );
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use aoc2021::rng::Rng;
+    use aoc2021::text::variants;
+
+    const SAMPLE: &str = "0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2";
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), 5);
+            assert_eq!(part_two(&input), 12);
+        }
+    }
+
+    #[test]
+    fn handles_huge_and_negative_coordinates() {
+        let input = "-5,-5 -> 5,5\n-5,5 -> 5,-5\n0,-3000 -> 0,3000\n\
+            4000000000,7 -> 4000000002,7\n4000000001,6 -> 4000000001,8";
+        let found = overlaps(&parse_lines(input), true, true);
+        assert_eq!(found.count, 2);
+        assert_eq!(found.max_depth, 3);
+        assert_eq!(
+            found.points,
+            vec![
+                Point { x: 0, y: 0 },
+                Point {
+                    x: 4000000001,
+                    y: 7
+                }
+            ]
+        );
+        assert_eq!(overlaps(&parse_lines(input), false, true).max_depth, 2);
+
+        let sample = overlaps(&parse_lines(SAMPLE), true, true);
+        assert_eq!(sample.max_depth, 3);
+        assert_eq!(sample.stretches[0], line("0,9 -> 2,9"));
+        assert_eq!(sample.all_points()[0], Point { x: 0, y: 9 });
+    }
+
+    fn line(input: &str) -> Line {
+        parse_lines(input)[0]
+    }
+
+    #[test]
+    fn plots_any_slope() {
+        assert_eq!(
+            line("0,0 -> 6,3").points(true).collect::<Vec<_>>(),
+            vec![
+                Point { x: 0, y: 0 },
+                Point { x: 2, y: 1 },
+                Point { x: 4, y: 2 },
+                Point { x: 6, y: 3 }
+            ]
+        );
+        assert_eq!(line("5,1 -> 1,2").points(true).count(), 2);
+        assert_eq!(line("5,1 -> 1,2").points(false).count(), 0);
+        assert_eq!(
+            line("3,3 -> 3,3").points(true).collect::<Vec<_>>(),
+            vec![Point { x: 3, y: 3 }]
+        );
+        assert_eq!(line("3,3 -> 3,3").points(false).count(), 0);
+        assert_eq!(
+            line("9,0 -> 0,6").points(false).collect::<Vec<_>>(),
+            vec![Point { x: 6, y: 2 }, Point { x: 3, y: 4 }]
+        );
+
+        // Both shallow lines pass through 3,1, only at an end for the second
+        let lines = parse_lines("0,0 -> 6,2\n3,1 -> 7,-1");
+        assert_eq!(
+            overlaps(&lines, true, true).points,
+            vec![Point { x: 3, y: 1 }]
+        );
+        assert_eq!(overlaps(&lines, true, false).count, 0);
+        assert_eq!(overlaps(&lines, false, true).count, 0);
+    }
+
+    #[test]
+    fn intersects_segments() {
+        assert_eq!(
+            line("0,0 -> 4,4").intersection(&line("0,4 -> 4,0")),
+            Intersection::Point(Point { x: 2, y: 2 })
+        );
+        assert_eq!(
+            line("0,0 -> 3,3").intersection(&line("0,3 -> 3,0")),
+            Intersection::OffGrid
+        );
+        assert_eq!(
+            line("0,0 -> 1,1").intersection(&line("0,3 -> 3,0")),
+            Intersection::None
+        );
+        assert_eq!(
+            line("0,0 -> 2,0").intersection(&line("0,1 -> 2,1")),
+            Intersection::None
+        );
+        assert_eq!(
+            line("0,0 -> 6,3").intersection(&line("8,4 -> 2,1")),
+            Intersection::Overlap(line("2,1 -> 6,3"))
+        );
+        assert_eq!(
+            line("0,0 -> 2,2").intersection(&line("2,2 -> 5,5")),
+            Intersection::Point(Point { x: 2, y: 2 })
+        );
+        assert_eq!(
+            line("1,1 -> 1,1").intersection(&line("0,0 -> 2,2")),
+            Intersection::Point(Point { x: 1, y: 1 })
+        );
+        assert_eq!(
+            line("9,9 -> 9,9").intersection(&line("0,0 -> 2,2")),
+            Intersection::None
+        );
+    }
+
+    #[test]
+    fn spans_the_whole_range() {
+        // Both ends are further apart than i64::MAX, which used to overflow
+        let wide = line("-9223372036854775808,0 -> 9223372036854775807,1");
+        assert_eq!(
+            wide.points(true).collect::<Vec<_>>(),
+            vec![wide.start, wide.end]
+        );
+        assert!(wide.contains(&wide.end));
+        assert!(!wide.contains(&Point { x: 0, y: 0 }));
+        assert!(line("0,0 -> 6,3").contains(&Point { x: 4, y: 2 }));
+        assert!(!line("0,0 -> 6,3").contains(&Point { x: 8, y: 4 }));
+        assert!(!line("0,0 -> 6,3").contains(&Point { x: -2, y: -1 }));
+        assert!(!line("0,0 -> 6,3").contains(&Point { x: 3, y: 1 }));
+
+        // Far too many points to hold at once, but walking a few is fine
+        let long = line("-9223372036854775808,5 -> 9223372036854775807,5");
+        assert_eq!(
+            long.points(false).nth(2),
+            Some(Point {
+                x: -9223372036854775805,
+                y: 5
+            })
+        );
+    }
+
+    #[test]
+    fn crosses_far_from_the_origin() {
+        // The cross products here are far past i128, let alone the products
+        // of them that finding the crossing used to take
+        let big = 20000000000000;
+        let a = Line {
+            start: Point { x: 0, y: 0 },
+            end: Point { x: big, y: big },
+        };
+        let b = Line {
+            start: Point { x: 0, y: big },
+            end: Point { x: big, y: 0 },
+        };
+        assert_eq!(
+            a.intersection(&b),
+            Intersection::Point(Point {
+                x: big / 2,
+                y: big / 2
+            })
+        );
+
+        let near = 1 << 62;
+        let a = Line {
+            start: Point { x: -near, y: -near },
+            end: Point {
+                x: near,
+                y: near - 2,
+            },
+        };
+        let b = Line {
+            start: Point {
+                x: -near,
+                y: near - 1,
+            },
+            end: Point {
+                x: near,
+                y: -near - 1,
+            },
+        };
+        assert_eq!(
+            a.intersection(&b),
+            Intersection::Point(Point { x: 0, y: -1 })
+        );
+
+        let max = line(
+            "-9223372036854775807,-9223372036854775807 -> 9223372036854775807,9223372036854775807",
+        );
+        let min = line(
+            "-9223372036854775807,9223372036854775807 -> 9223372036854775807,-9223372036854775807",
+        );
+        assert_eq!(
+            max.intersection(&min),
+            Intersection::Point(Point { x: 0, y: 0 })
+        );
+        let corners = line(
+            "-9223372036854775808,-9223372036854775808 -> 9223372036854775807,9223372036854775807",
+        );
+        let across = line(
+            "-9223372036854775808,9223372036854775807 -> 9223372036854775807,-9223372036854775808",
+        );
+        assert_eq!(corners.intersection(&across), Intersection::OffGrid);
+        assert_eq!(
+            corners.intersection(&line("9223372036854775807,0 -> 9223372036854775806,0")),
+            Intersection::None
+        );
+
+        // Collinear stretches come out in the order the first line runs
+        assert_eq!(
+            line("-9223372036854775808,0 -> 0,0")
+                .intersection(&line("9223372036854775807,0 -> -5,0")),
+            Intersection::Overlap(line("-5,0 -> 0,0"))
+        );
+        assert_eq!(
+            line("0,0 -> -9223372036854775808,0")
+                .intersection(&line("9223372036854775807,0 -> -5,0")),
+            Intersection::Overlap(line("0,0 -> -5,0"))
+        );
+    }
+
+    #[test]
+    fn counts_long_overlaps_without_walking_them() {
+        let input = "-9000000000000000000,0 -> 9000000000000000000,0
+0,0 -> 9000000000000000000,0
+5,5 -> 5,-5
+-3,3 -> 3,-3
+-1000000000000000000,1 -> 1000000000000000000,-1
+7,-9000000000000000000 -> 7,9000000000000000000
+7,1 -> 7,-1";
+        let lines = parse_lines(input);
+        let found = overlaps(&lines, true, true);
+        // The stretch along y = 0 and three more down x = 7, one of which
+        // was already counted
+        assert_eq!(found.count, 9000000000000000001 + 2);
+        assert_eq!(found.max_depth, 4);
+        assert_eq!(found.points, vec![]);
+        assert_eq!(
+            found.stretches,
+            vec![line("0,0 -> 9000000000000000000,0"), line("7,-1 -> 7,1")]
+        );
+
+        // Without the ends x = 7 is down to one crossing on the stretch
+        let found = overlaps(&lines, true, false);
+        assert_eq!(found.count, 9000000000000000000 - 1 + 1);
+        assert_eq!(found.max_depth, 4);
+        assert_eq!(found.points, vec![Point { x: 0, y: 0 }]);
+        assert_eq!(found.stretches, vec![line("1,0 -> 8999999999999999999,0")]);
+    }
+
+    #[test]
+    fn matches_walking_every_point() {
+        // Small random vents at every angle, checked against plotting them
+        let mut rng = Rng::new(5);
+        for _ in 0..100 {
+            let mut coord = || rng.below(13) as i64 - 6;
+            let lines: Vec<Line> = (0..8)
+                .map(|_| Line {
+                    start: Point {
+                        x: coord(),
+                        y: coord(),
+                    },
+                    end: Point {
+                        x: coord(),
+                        y: coord(),
+                    },
+                })
+                .collect();
+            for with_endpoints in [true, false] {
+                let mut board: HashMap<Point, u32> = HashMap::new();
+                for line in &lines {
+                    for point in line.points(with_endpoints) {
+                        *board.entry(point).or_insert(0) += 1;
+                    }
+                }
+                let mut walked: Vec<Point> = board
+                    .iter()
+                    .filter(|(_, n)| **n >= 2)
+                    .map(|(p, _)| *p)
+                    .collect();
+                walked.sort();
+
+                let found = overlaps(&lines, true, with_endpoints);
+                assert_eq!(found.all_points(), walked, "{:?}", lines);
+                assert_eq!(found.count, walked.len() as u128);
+                assert_eq!(
+                    found.max_depth,
+                    board.values().copied().max().unwrap_or(0),
+                    "{:?}",
+                    lines
+                );
+            }
+        }
+    }
 }
@@ -1,45 +1,182 @@
-fn run_and_simulate(input: &str, simulate_for: u64) -> u64 {
-    let fish: Vec<u64> = input
-        .split(",")
-        .map(|f| f.parse::<u64>().unwrap())
+use aoc2021::bigint::BigUint;
+use aoc2021::text;
+use std::env;
+
+// Timers for a species. A fish whose timer is at 0 goes back to `reset`
+// the next day and has a newborn starting on `newborn`. The starting school
+// gets `delay` added to its timers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Species {
+    reset: usize,
+    newborn: usize,
+    delay: usize,
+}
+
+const LANTERNFISH: Species = Species {
+    reset: 6,
+    newborn: 8,
+    delay: 0,
+};
+
+// How many fish there are on each timer value, long enough for every timer
+// the species or the input can have
+fn parse_timers(input: &str, species: &Species) -> Vec<BigUint> {
+    let timers: Vec<usize> = text::fields(input, ',')
+        .iter()
+        .map(|f| f.parse::<usize>().unwrap() + species.delay)
         .collect();
+    let slots = timers
+        .iter()
+        .copied()
+        .chain([species.reset, species.newborn])
+        .max()
+        .unwrap()
+        + 1;
 
-    let mut days: Vec<u64> = vec![0; 9];
-    for i in fish {
-        days[i as usize] += 1;
+    let mut rv = vec![BigUint::zero(); slots];
+    for timer in timers {
+        rv[timer] += BigUint::one();
     }
+    rv
+}
 
-    let mut day: u64 = 0;
-    loop {
-        // Which index is about to birth new fish
-        let birth_idx = day % 9;
+fn next_day(counts: &[BigUint], species: &Species) -> Vec<BigUint> {
+    let mut rv = counts[1..].to_vec();
+    rv.push(BigUint::zero());
+    rv[species.reset] += &counts[0];
+    rv[species.newborn] += &counts[0];
+    rv
+}
 
-        // Which index new fish will go in
-        let reset_idx = (day + 7) % 9;
+// The timer counts on every day from the start up to `days`, so the first
+// entry is the input as given
+fn history(input: &str, species: &Species, days: u64) -> Vec<Vec<BigUint>> {
+    // Counts get enormous for long runs, so they're big integers
+    let mut rv = vec![parse_timers(input, species)];
+    for _ in 0..days {
+        let next = next_day(rv.last().unwrap(), species);
+        rv.push(next);
+    }
+    rv
+}
+
+fn run_and_simulate(input: &str, simulate_for: u64) -> BigUint {
+    let mut counts = parse_timers(input, &LANTERNFISH);
+    for _ in 0..simulate_for {
+        counts = next_day(&counts, &LANTERNFISH);
+    }
+    counts.iter().sum()
+}
 
-        // Any fish that are on the birth index get added to
-        // the reset index, this is the only thing we have to do ??
-        days[reset_idx as usize] += days[birth_idx as usize];
+// Square matrix of counts. With a modulus every product is reduced as it
+// goes, so the numbers never get bigger than the modulus squared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Matrix {
+    size: usize,
+    cells: Vec<BigUint>,
+    modulus: Option<u64>,
+}
 
-        // Increase simulation counter and exit if we're at breakpoint
-        day += 1;
-        if day == simulate_for {
-            break;
+impl Matrix {
+    fn identity(size: usize, modulus: Option<u64>) -> Matrix {
+        let mut cells = vec![BigUint::zero(); size * size];
+        for idx in 0..size {
+            cells[idx * size + idx] = BigUint::one();
+        }
+        Matrix {
+            size,
+            cells,
+            modulus,
         }
     }
 
-    let mut counter = 0;
-    for day in days {
-        counter += day;
+    fn reduce(&self, value: BigUint) -> BigUint {
+        match self.modulus {
+            Some(m) => BigUint::from(value.rem_u64(m)),
+            None => value,
+        }
+    }
+
+    fn mul(&self, other: &Matrix) -> Matrix {
+        let n = self.size;
+        let mut cells = Vec::with_capacity(n * n);
+        for row in 0..n {
+            for col in 0..n {
+                let sum = (0..n)
+                    .map(|k| &self.cells[row * n + k] * &other.cells[k * n + col])
+                    .sum();
+                cells.push(self.reduce(sum));
+            }
+        }
+        Matrix {
+            size: n,
+            cells,
+            modulus: self.modulus,
+        }
+    }
+
+    // By squaring, so only about 2 log2(exp) multiplications
+    fn pow(&self, mut exp: u64) -> Matrix {
+        let mut rv = Matrix::identity(self.size, self.modulus);
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                rv = rv.mul(&base);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul(&base);
+            }
+        }
+        rv
+    }
+
+    fn apply(&self, counts: &[BigUint]) -> Vec<BigUint> {
+        let n = self.size;
+        (0..n)
+            .map(|row| {
+                let sum = (0..n).map(|k| &self.cells[row * n + k] * &counts[k]).sum();
+                self.reduce(sum)
+            })
+            .collect()
     }
-    counter
 }
 
-fn part_one(input: &str) -> u64 {
+// One day as a matrix on the timer counts, `next_day` written out
+fn one_day(size: usize, species: &Species, modulus: Option<u64>) -> Matrix {
+    let mut rv = Matrix {
+        size,
+        cells: vec![BigUint::zero(); size * size],
+        modulus,
+    };
+    for timer in 1..size {
+        rv.cells[(timer - 1) * size + timer] = BigUint::one();
+    }
+    rv.cells[species.reset * size] += BigUint::one();
+    rv.cells[species.newborn * size] += BigUint::one();
+    rv
+}
+
+// The population after any number of days in O(log days) matrix products,
+// exact or modulo `modulus`. Exact answers still grow by a digit every 27
+// days or so, past a few million days only the modulo one is practical.
+fn population(input: &str, species: &Species, days: u64, modulus: Option<u64>) -> BigUint {
+    let start = parse_timers(input, species);
+    let counts = one_day(start.len(), species, modulus)
+        .pow(days)
+        .apply(&start);
+    let total: BigUint = counts.iter().sum();
+    match modulus {
+        Some(m) => BigUint::from(total.rem_u64(m)),
+        None => total,
+    }
+}
+
+fn part_one(input: &str) -> BigUint {
     run_and_simulate(input, 80)
 }
 
-fn part_two(input: &str) -> u64 {
+fn part_two(input: &str) -> BigUint {
     run_and_simulate(input, 256)
 }
 
@@ -47,4 +184,132 @@ fn main() {
     let input = include_str!("day6.txt");
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
+
+    // `cargo run --bin day6 -- 10000` to keep going for longer, with a
+    // modulus after the days for runs too long to count exactly.
+    // `history <days> [reset newborn delay]` prints the population and the
+    // count on each timer for every day, for charting other species.
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(|a| a.as_str()) {
+        Some("history") => {
+            let days = args[2].parse::<u64>().unwrap();
+            let species = match &args[3..] {
+                [reset, newborn, delay] => Species {
+                    reset: reset.parse().unwrap(),
+                    newborn: newborn.parse().unwrap(),
+                    delay: delay.parse().unwrap(),
+                },
+                _ => LANTERNFISH,
+            };
+            for (day, counts) in history(input, &species, days).iter().enumerate() {
+                let ages: Vec<String> = counts.iter().map(|c| c.to_string()).collect();
+                println!(
+                    "DAY {}: {} [{}]",
+                    day,
+                    counts.iter().sum::<BigUint>(),
+                    ages.join(",")
+                );
+            }
+        }
+        Some(days) => {
+            let days = days.parse::<u64>().unwrap();
+            let modulus = args.get(2).map(|m| m.parse::<u64>().unwrap());
+            println!(
+                "DAY {}: {}",
+                days,
+                population(input, &LANTERNFISH, days, modulus)
+            );
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use aoc2021::bigint::BigUint;
+    use aoc2021::text::variants;
+
+    #[test]
+    fn it_works() {
+        for input in variants("3,4,3,1,2") {
+            assert_eq!(part_one(&input), BigUint::from(5934u64));
+            assert_eq!(part_two(&input), BigUint::from(26984457539u64));
+        }
+    }
+
+    #[test]
+    fn runs_past_u64() {
+        assert_eq!(run_and_simulate("3,4,3,1,2", 0), BigUint::from(5u64));
+        assert_eq!(run_and_simulate("3,4,3,1,2", 18), BigUint::from(26u64));
+        // Roughly where a u64 counter used to overflow
+        assert!(run_and_simulate("3,4,3,1,2", 500).bits() > 64);
+    }
+
+    #[test]
+    fn matches_daily_simulation() {
+        for days in [0, 1, 18, 80, 256, 1000] {
+            assert_eq!(
+                population("3,4,3,1,2", &LANTERNFISH, days, None),
+                run_and_simulate("3,4,3,1,2", days)
+            );
+            assert_eq!(
+                population("3,4,3,1,2", &LANTERNFISH, days, Some(1_000_000_007)),
+                BigUint::from(run_and_simulate("3,4,3,1,2", days).rem_u64(1_000_000_007))
+            );
+        }
+
+        // Way past anything a day by day loop could do
+        let huge = population(
+            "3,4,3,1,2",
+            &LANTERNFISH,
+            1_000_000_000_000_000,
+            Some(1_000_000_007),
+        );
+        assert!(huge < BigUint::from(1_000_000_007u64));
+        assert_eq!(
+            population("3,4,3,1,2", &LANTERNFISH, 10, Some(1)),
+            BigUint::zero()
+        );
+    }
+
+    #[test]
+    fn models_other_species() {
+        let days = history("3,4,3,1,2", &LANTERNFISH, 18);
+        assert_eq!(days.len(), 19);
+        assert_eq!(days[0][3], BigUint::from(2u64));
+        assert_eq!(days[18].iter().sum::<BigUint>(), BigUint::from(26u64));
+
+        // Reproduces every other day, newborns wait one extra day
+        let quick = Species {
+            reset: 1,
+            newborn: 2,
+            delay: 0,
+        };
+        let sizes: Vec<u64> = history("0", &quick, 6)
+            .iter()
+            .map(|c| c.iter().sum::<BigUint>().to_u64().unwrap())
+            .collect();
+        assert_eq!(sizes, vec![1, 2, 2, 3, 4, 5, 7]);
+
+        // A delay just shifts the whole curve along
+        let late = Species {
+            delay: 3,
+            ..LANTERNFISH
+        };
+        assert_eq!(
+            population("3,4,3,1,2", &late, 83, None),
+            run_and_simulate("3,4,3,1,2", 80)
+        );
+        for days in [0, 5, 40] {
+            assert_eq!(
+                population("0", &quick, days, None),
+                history("0", &quick, days)
+                    .last()
+                    .unwrap()
+                    .iter()
+                    .sum::<BigUint>()
+            );
+        }
+    }
 }
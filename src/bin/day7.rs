@@ -1,62 +1,229 @@
-use std::cmp::{max, min};
-use std::collections::HashMap;
-
-fn fuel_calc(from_pos: u32, to_pos: u32, part: u32) -> u32 {
-    let min_pos = min(from_pos, to_pos);
-    let max_pos = max(from_pos, to_pos);
-    let delta: f32 = max_pos as f32 - min_pos as f32;
-
-    if part == 1 {
-        delta as u32
-    } else {
-        let result = (delta / 2 as f32) * (1 + (max_pos - min_pos)) as f32;
-        result as u32
-    }
-}
-
-fn move_crabs(input: &str, part: u32) -> u32 {
-    let nums: Vec<u32> = input
-        .split(",")
-        .map(|f| f.parse::<u32>().unwrap())
-        .collect();
-
-    let mut crabs: HashMap<u32, u32> = HashMap::new();
-    let mut min_pos = 24092834;
-    let mut max_pos = 0;
-    for num in nums {
-        let count = crabs.entry(num).or_insert(0);
-        *count += 1;
-        min_pos = min(num, min_pos);
-        max_pos = max(num, max_pos);
-    }
-
-    let mut min_fuel = 230400234;
-
-    for idx in min_pos..max_pos + 1 {
-        let mut fuel = 0;
-        for (crab, count) in &crabs {
-            if *crab == idx {
-                continue;
-            } else if *crab > idx {
-                fuel += fuel_calc(*crab, idx, part) * count
-            } else {
-                fuel += fuel_calc(*crab, idx, part) * count
+use aoc2021::text;
+use std::collections::{HashMap, HashSet};
+use std::env;
+
+// How many crabs are at each position. A crab with a fuel multiplier counts
+// as that many crabs, since its fuel goes up the same way.
+type Crabs = HashMap<u32, u32>;
+
+// Fuel for one crab to move `distance`, all in integers so long distances
+// come out exact. The optimiser assumes the cost only ever grows faster as
+// the distance goes up (convex), which all of these do.
+trait Cost {
+    fn fuel(&self, distance: u64) -> u64;
+
+    // Positions known to hold the best alignment, if the cost has a shortcut
+    fn guesses(&self, _crabs: &Crabs) -> Option<Vec<u32>> {
+        None
+    }
+}
+
+struct Linear;
+
+impl Cost for Linear {
+    fn fuel(&self, distance: u64) -> u64 {
+        distance
+    }
+
+    // Moving towards the median never costs more than it saves
+    fn guesses(&self, crabs: &Crabs) -> Option<Vec<u32>> {
+        let total: u64 = crabs.values().map(|c| *c as u64).sum();
+        let mut positions: Vec<&u32> = crabs.keys().collect();
+        positions.sort();
+
+        let mut seen = 0;
+        for pos in positions {
+            seen += crabs[pos] as u64;
+            if seen * 2 >= total {
+                return Some(vec![*pos]);
             }
         }
-        if fuel < min_fuel {
-            min_fuel = fuel;
+        None
+    }
+}
+
+// 1 + 2 + ... + distance
+struct Triangular;
+
+impl Cost for Triangular {
+    fn fuel(&self, distance: u64) -> u64 {
+        distance * (distance + 1) / 2
+    }
+
+    // The best spot is always within half a step of the mean
+    fn guesses(&self, crabs: &Crabs) -> Option<Vec<u32>> {
+        mean_neighbours(crabs)
+    }
+}
+
+struct Quadratic;
+
+impl Cost for Quadratic {
+    fn fuel(&self, distance: u64) -> u64 {
+        distance * distance
+    }
+
+    fn guesses(&self, crabs: &Crabs) -> Option<Vec<u32>> {
+        mean_neighbours(crabs)
+    }
+}
+
+// Anything else, searched for rather than guessed
+impl<F: Fn(u64) -> u64> Cost for F {
+    fn fuel(&self, distance: u64) -> u64 {
+        self(distance)
+    }
+}
+
+fn mean_neighbours(crabs: &Crabs) -> Option<Vec<u32>> {
+    let total: u64 = crabs.values().map(|c| *c as u64).sum();
+    let sum: u64 = crabs.iter().map(|(p, c)| *p as u64 * *c as u64).sum();
+    let mean = sum.checked_div(total)? as u32;
+    Some(vec![mean, mean + 1])
+}
+
+// Either `pos` or `pos*multiplier` for crabs that burn more fuel
+fn parse_crabs(input: &str) -> Crabs {
+    let mut crabs = Crabs::new();
+    for field in text::fields(input, ',') {
+        let (pos, weight) = field.split_once('*').unwrap_or((field, "1"));
+        *crabs.entry(pos.parse::<u32>().unwrap()).or_insert(0) += weight.parse::<u32>().unwrap();
+    }
+    crabs
+}
+
+fn total_fuel<C: Cost>(crabs: &Crabs, cost: &C, target: u32) -> u64 {
+    crabs
+        .iter()
+        .map(|(pos, count)| cost.fuel(pos.abs_diff(target) as u64) * *count as u64)
+        .sum()
+}
+
+// The cheapest position and what it costs. Costs with a shortcut only try
+// their guesses, the rest get a ternary search, done as a binary search for
+// where the total stops going down since that's the same thing for convex
+// costs and simpler on integers.
+fn align<C: Cost>(crabs: &Crabs, cost: &C) -> (u32, u64) {
+    if let Some(guesses) = cost.guesses(crabs) {
+        return guesses
+            .into_iter()
+            .map(|pos| (pos, total_fuel(crabs, cost, pos)))
+            .min_by_key(|(pos, fuel)| (*fuel, *pos))
+            .unwrap();
+    }
+
+    let mut lo = *crabs.keys().min().unwrap();
+    let mut hi = *crabs.keys().max().unwrap();
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if total_fuel(crabs, cost, mid) <= total_fuel(crabs, cost, mid + 1) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    (lo, total_fuel(crabs, cost, lo))
+}
+
+// Like `align` but never on a blocked position. The total only rises moving
+// away from the best spot, so the answer is the first free position on one
+// side of it or the other.
+fn align_avoiding<C: Cost>(crabs: &Crabs, cost: &C, blocked: &HashSet<u32>) -> (u32, u64) {
+    let (best, fuel) = align(crabs, cost);
+    if !blocked.contains(&best) {
+        return (best, fuel);
+    }
+
+    let below = (0..best).rev().find(|p| !blocked.contains(p));
+    let above = (best + 1..).find(|p| !blocked.contains(p));
+    [below, above]
+        .into_iter()
+        .flatten()
+        .map(|pos| (pos, total_fuel(crabs, cost, pos)))
+        .min_by_key(|(pos, fuel)| (*fuel, *pos))
+        .unwrap()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Group {
+    position: u32,
+    // Counting multipliers, like the histogram
+    crabs: u32,
+    fuel: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct Plan {
+    groups: Vec<Group>,
+    fuel: u64,
+}
+
+// Splits the crabs into at most `targets` groups that each line up on their
+// own position. With a convex cost each group is a run of neighbouring
+// positions, so it's a DP over where each run ends, pricing every run once.
+fn plan<C: Cost>(crabs: &Crabs, cost: &C, blocked: &HashSet<u32>, targets: usize) -> Plan {
+    let mut positions: Vec<u32> = crabs.keys().copied().collect();
+    positions.sort();
+    let n = positions.len();
+    let targets = targets.min(n);
+    if targets == 0 {
+        return Plan::default();
+    }
+
+    // runs[from][len - 1] lines up the `len` positions starting at `from`
+    let mut runs = Vec::with_capacity(n);
+    for from in 0..n {
+        let mut group = Crabs::new();
+        let mut row = Vec::with_capacity(n - from);
+        for pos in &positions[from..] {
+            group.insert(*pos, crabs[pos]);
+            let (position, fuel) = align_avoiding(&group, cost, blocked);
+            row.push(Group {
+                position,
+                crabs: group.values().sum(),
+                fuel,
+            });
+        }
+        runs.push(row);
+    }
+
+    // best[g][i] is the cheapest way to cover the first i positions with g
+    // groups, and where its last group starts
+    let mut best = vec![vec![None; n + 1]; targets + 1];
+    best[0][0] = Some((0, 0));
+    for g in 1..=targets {
+        for i in g..=n {
+            best[g][i] = (g - 1..i)
+                .filter_map(|j| best[g - 1][j].map(|(f, _)| (f + runs[j][i - 1 - j].fuel, j)))
+                .min();
         }
     }
 
-    min_fuel
+    let mut groups = Vec::new();
+    let mut end = n;
+    for g in (1..=targets).rev() {
+        let (_, start) = best[g][end].unwrap();
+        groups.push(runs[start][end - 1 - start]);
+        end = start;
+    }
+    groups.reverse();
+
+    Plan {
+        fuel: groups.iter().map(|g| g.fuel).sum(),
+        groups,
+    }
+}
+
+fn move_crabs<C: Cost>(input: &str, cost: &C) -> u64 {
+    align(&parse_crabs(input), cost).1
 }
 
-fn part_one(input: &str) -> u32 {
-    move_crabs(input, 1)
+fn part_one(input: &str) -> u64 {
+    move_crabs(input, &Linear)
 }
 
-fn part_two(input: &str) -> u32 {
-    move_crabs(input, 2)
+fn part_two(input: &str) -> u64 {
+    move_crabs(input, &Triangular)
 }
 
 fn main() {
@@ -64,4 +231,151 @@ fn main() {
 
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
+
+    // `align <linear|triangular|quadratic> [groups] [blocked,positions]`
+    // shows where the crabs end up, split into groups if asked
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|a| a.as_str()) == Some("align") {
+        let crabs = parse_crabs(input);
+        let targets = args.get(3).map_or(1, |a| a.parse::<usize>().unwrap());
+        let blocked: HashSet<u32> = args.get(4).map_or(HashSet::new(), |a| {
+            text::fields(a, ',')
+                .iter()
+                .map(|p| p.parse::<u32>().unwrap())
+                .collect()
+        });
+        let found = match args[2].as_str() {
+            "linear" => plan(&crabs, &Linear, &blocked, targets),
+            "triangular" => plan(&crabs, &Triangular, &blocked, targets),
+            "quadratic" => plan(&crabs, &Quadratic, &blocked, targets),
+            other => panic!("unknown cost {:?}", other),
+        };
+        for group in &found.groups {
+            println!(
+                "ALIGN: {} crabs to position {}, fuel {}",
+                group.crabs, group.position, group.fuel
+            );
+        }
+        println!("TOTAL: {}", found.fuel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use aoc2021::text::variants;
+
+    const SAMPLE: &str = "16,1,2,0,4,2,7,1,2,14";
+
+    // Every position in range, the way this used to be done
+    fn brute_force<C: Cost>(crabs: &Crabs, cost: &C) -> u64 {
+        let lo = *crabs.keys().min().unwrap();
+        let hi = *crabs.keys().max().unwrap();
+        (lo..=hi).map(|p| total_fuel(crabs, cost, p)).min().unwrap()
+    }
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), 37);
+            assert_eq!(part_two(&input), 168);
+        }
+    }
+
+    #[test]
+    fn finds_the_best_position() {
+        let crabs = parse_crabs(SAMPLE);
+        assert_eq!(align(&crabs, &Linear), (2, 37));
+        assert_eq!(align(&crabs, &Triangular), (5, 168));
+        assert_eq!(align(&crabs, &Quadratic).1, brute_force(&crabs, &Quadratic));
+
+        let cubic = |d: u64| d * d * d;
+        assert_eq!(align(&crabs, &cubic).1, brute_force(&crabs, &cubic));
+
+        let skewed = parse_crabs("0,0,0,0,0,0,0,0,0,1000");
+        assert_eq!(
+            align(&skewed, &Triangular).1,
+            brute_force(&skewed, &Triangular)
+        );
+        assert_eq!(
+            align(&skewed, &Quadratic).1,
+            brute_force(&skewed, &Quadratic)
+        );
+        assert_eq!(align(&skewed, &Linear), (0, 1000));
+    }
+
+    #[test]
+    fn stays_exact_over_long_distances() {
+        let crabs = parse_crabs("0,100000000");
+        assert_eq!(
+            align(&crabs, &Triangular),
+            (50000000, 2 * (50000000 * 50000001 / 2))
+        );
+        assert_eq!(align(&crabs, &Quadratic).1, 2 * 50000000 * 50000000);
+    }
+
+    #[test]
+    fn weights_and_blocks_positions() {
+        let crabs = parse_crabs("16*2,1,2,0,4,2*3,7,1,2,14");
+        assert_eq!(crabs[&2], 5);
+        assert_eq!(crabs[&16], 2);
+        assert_eq!(
+            align(&crabs, &Linear),
+            align(&parse_crabs("16,16,1,2,0,4,2,2,2,7,1,2,14"), &Linear)
+        );
+
+        let crabs = parse_crabs(SAMPLE);
+        let blocked: HashSet<u32> = [1, 2, 3].into_iter().collect();
+        let (pos, fuel) = align_avoiding(&crabs, &Linear, &blocked);
+        let expected = (0..=16)
+            .filter(|p| !blocked.contains(p))
+            .map(|p| total_fuel(&crabs, &Linear, p))
+            .min()
+            .unwrap();
+        assert_eq!(fuel, expected);
+        assert_eq!(pos, 4);
+        assert_eq!(
+            align_avoiding(&crabs, &Triangular, &HashSet::new()),
+            (5, 168)
+        );
+    }
+
+    #[test]
+    fn splits_into_groups() {
+        let crabs = parse_crabs("0,0,1,10,11,11");
+        let none = HashSet::new();
+        assert_eq!(
+            plan(&crabs, &Linear, &none, 1).fuel,
+            align(&crabs, &Linear).1
+        );
+        assert_eq!(
+            plan(&crabs, &Linear, &none, 2),
+            Plan {
+                groups: vec![
+                    Group {
+                        position: 0,
+                        crabs: 3,
+                        fuel: 1
+                    },
+                    Group {
+                        position: 11,
+                        crabs: 3,
+                        fuel: 1
+                    }
+                ],
+                fuel: 2
+            }
+        );
+        assert_eq!(plan(&crabs, &Linear, &none, 10).fuel, 0);
+        assert_eq!(plan(&crabs, &Linear, &none, 10).groups.len(), 4);
+
+        let blocked: HashSet<u32> = [0, 11].into_iter().collect();
+        let found = plan(&crabs, &Triangular, &blocked, 2);
+        assert_eq!(found.groups[0].position, 1);
+        assert_eq!(found.groups[1].position, 10);
+        assert_eq!(found.fuel, 1 + 1 + 1 + 1);
+
+        let sample = parse_crabs(SAMPLE);
+        assert!(plan(&sample, &Triangular, &none, 3).fuel < 168);
+    }
 }
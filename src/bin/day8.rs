@@ -1,33 +1,13 @@
-use std::{
-    collections::{HashMap, HashSet},
-    io::Read,
-};
-
-fn segmentize(segment: &str) -> u8 {
-    let mut rv = 0;
-
-    for char in segment.split("") {
-        match char {
-            "a" => rv = 0,
-            "b" => rv = 1,
-            "c" => rv = 2,
-            "d" => rv = 3,
-            "e" => rv = 4,
-            "f" => rv = 5,
-            "g" => rv = 6,
-            _ => panic!("nope"),
-        };
-    }
-
-    rv
-}
+use aoc2021::text;
+use std::env;
+use std::io::{self, BufRead};
 
 fn digit_to_segments(digit: u8) -> Vec<u8> {
     match digit {
         0 => vec![0, 1, 2, 4, 5, 6],    //vec!["a", "b", "c", "e", "f", "g"],
         1 => vec![2, 5],                //vec!["c", "f"],
         2 => vec![0, 2, 3, 4, 6],       //vec!["a", "c", "d", "e", "g"],
-        3 => vec![0, 2, 3, 4, 5, 6],    //vec!["a", "c", "d", "e", "f", "g"],
+        3 => vec![0, 2, 3, 5, 6],       //vec!["a", "c", "d", "f", "g"],
         4 => vec![1, 2, 3, 5],          //vec!["b", "c", "d", "f"],
         5 => vec![0, 1, 3, 5, 6],       //vec!["a", "b", "d", "f", "g"],
         6 => vec![0, 1, 3, 4, 5, 6],    //vec!["a", "b", "d", "e", "f", "g"],
@@ -38,37 +18,9 @@ fn digit_to_segments(digit: u8) -> Vec<u8> {
     }
 }
 
-fn ids_to_possibles(digit: &str) -> Vec<u8> {
-    let mut rv = Vec::new();
-
-    match digit.len() {
-        2 => rv.push(1),
-        3 => rv.push(7),
-        4 => rv.push(4),
-        5 => {
-            rv.push(2);
-            rv.push(3);
-            rv.push(5);
-        }
-        6 => {
-            rv.push(0);
-            rv.push(6);
-            rv.push(9);
-        }
-        7 => rv.push(8),
-        _ => panic!("no matches"),
-    }
-
-    rv
-}
-
-fn digitize(segment: &str) -> HashSet<&str> {
-    HashSet::from_iter(segment.split("").filter(|f| *f != ""))
-}
-
 fn part_one(input: &str) -> u32 {
     let mut rv = 0;
-    for line in input.split("\n") {
+    for line in text::lines(input) {
         let parts: Vec<&str> = line.split(" | ").collect();
         for part in parts[1].split(" ") {
             match part.len() {
@@ -84,255 +36,631 @@ fn part_one(input: &str) -> u32 {
     rv
 }
 
-/*
-      0
-    1   2
-      3
-    4   5
-      6
-
-dafe fe cef agdebfc fecdg bedcg bdcafg beafcg dcegaf fdcag
-
-    fe => { f => {2, 5}, e => {2, 5} }
-    cef => { c => {0}, e => {2, 5}, f => {2, 5} }
-    dafe => { e => {2, 5}, f => {2, 5}, d => {1, 3}, a => {1, 3} }
-
-    fe => { 2 => { f, e }, 5 => { f, e } }
-    cef => { 0 => c, 2 => { f, e }, 5 => { f, e } }
-    dafe => { 2 => { f, e }, 5 => { f, e }, 3 => { d, a }, 1 => { d, a } }
-    fecdg =>
-        { 0 c, 2 f e, 3 4 6 }
-
-*/
-
-fn validate_number(number: u8) -> u8 {
-    match number {
-        0b01110111u8 => 0,
-        0b00100100u8 => 1,
-        0b01011101u8 => 2,
-        0b01101101u8 => 3,
-        0b00101110u8 => 4,
-        0b01101011u8 => 5,
-        0b01111011u8 => 6,
-        0b00100101u8 => 7,
-        0b01111111u8 => 8,
-        0b01101111u8 => 9,
-        _ => 255,
-    }
+// What a display can show: how many segments it has and which of them each
+// symbol lights. The seven segment ones are numbered
+//       0
+//     1   2
+//       3
+//     4   5
+//       6
+#[derive(Debug, Clone)]
+struct Display {
+    segments: usize,
+    symbols: Vec<(char, u32)>,
 }
 
-fn to_number(mapping: &HashMap<&str, u8>, number: &str) -> u8 {
-    // Convert a set of segments to a number based on what lights up
-    let mut num = 0;
-    for dig in number.split("").filter(|s| *s != "") {
-        if !mapping.contains_key(dig) {
-            panic!("segment not in map");
+// Fourteen segments, numbered differently from the seven segment diagram
+// above: the outline goes round clockwise from the top like the usual a to
+// f labels, the middle is split in two, and the diagonals and centre
+// verticals sit in between
+//      ----0----
+//     |\   |   /|
+//     5 8  9 10 1
+//     |  \ | /  |
+//      --6-- --7--
+//     |  / | \  |
+//     4 11 12 13 2
+//     |/   |   \|
+//      ----3----
+const FOURTEEN_SEGMENT: [(char, &[usize]); 36] = [
+    ('0', &[0, 1, 2, 3, 4, 5, 10, 11]),
+    ('1', &[1, 2, 10]),
+    ('2', &[0, 1, 3, 4, 6, 7]),
+    ('3', &[0, 1, 2, 3, 7]),
+    ('4', &[1, 2, 5, 6, 7]),
+    ('5', &[0, 2, 3, 5, 6, 7]),
+    ('6', &[0, 2, 3, 4, 5, 6, 7]),
+    ('7', &[0, 1, 2]),
+    ('8', &[0, 1, 2, 3, 4, 5, 6, 7]),
+    ('9', &[0, 1, 2, 3, 5, 6, 7]),
+    ('A', &[0, 1, 2, 4, 5, 6, 7]),
+    ('B', &[0, 1, 2, 3, 7, 9, 12]),
+    ('C', &[0, 3, 4, 5]),
+    ('D', &[0, 1, 2, 3, 9, 12]),
+    ('E', &[0, 3, 4, 5, 6]),
+    ('F', &[0, 4, 5, 6]),
+    ('G', &[0, 2, 3, 4, 5, 7]),
+    ('H', &[1, 2, 4, 5, 6, 7]),
+    ('I', &[0, 3, 9, 12]),
+    ('J', &[1, 2, 3, 4]),
+    ('K', &[4, 5, 6, 10, 13]),
+    ('L', &[3, 4, 5]),
+    ('M', &[1, 2, 4, 5, 8, 10]),
+    ('N', &[1, 2, 4, 5, 8, 13]),
+    ('O', &[0, 1, 2, 3, 4, 5]),
+    ('P', &[0, 1, 4, 5, 6, 7]),
+    ('Q', &[0, 1, 2, 3, 4, 5, 13]),
+    ('R', &[0, 1, 4, 5, 6, 7, 13]),
+    ('S', &[0, 2, 3, 7, 8]),
+    ('T', &[0, 9, 12]),
+    ('U', &[1, 2, 3, 4, 5]),
+    ('V', &[4, 5, 10, 11]),
+    ('W', &[1, 2, 4, 5, 11, 13]),
+    ('X', &[8, 10, 11, 13]),
+    ('Y', &[8, 10, 12]),
+    ('Z', &[0, 3, 10, 11]),
+];
+
+fn mask(segments: &[usize]) -> u32 {
+    segments.iter().fold(0, |rv, s| rv | 1 << s)
+}
+
+impl Display {
+    fn new(segments: usize, symbols: &[(char, &[usize])]) -> Display {
+        Display {
+            segments,
+            symbols: symbols.iter().map(|(c, s)| (*c, mask(s))).collect(),
         }
-        num += 1 << mapping[dig];
     }
-    validate_number(num)
-}
 
-fn part_two(input: &str) -> u32 {
-    let mut rv = 0;
-    let all_segments: HashSet<&str> = HashSet::from_iter(["a", "b", "c", "d", "e", "f", "g"]);
+    fn seven_segment() -> Display {
+        Display {
+            segments: 7,
+            symbols: (0..10)
+                .map(|d| {
+                    let segments: Vec<usize> =
+                        digit_to_segments(d).iter().map(|s| *s as usize).collect();
+                    ((b'0' + d) as char, mask(&segments))
+                })
+                .collect(),
+        }
+    }
 
-    for line in input.split("\n") {
-        let mut mappings: HashMap<&str, u8> = HashMap::new();
-        let mut known_segments: HashSet<&str> = HashSet::new();
+    // The digits plus A to F, with b and d in lower case like calculators do
+    fn hexadecimal() -> Display {
+        let mut rv = Display::seven_segment();
+        let letters: [(char, &[usize]); 6] = [
+            ('A', &[0, 1, 2, 3, 4, 5]),
+            ('b', &[1, 3, 4, 5, 6]),
+            ('C', &[0, 1, 4, 6]),
+            ('d', &[2, 3, 4, 5, 6]),
+            ('E', &[0, 1, 3, 4, 6]),
+            ('F', &[0, 1, 3, 4]),
+        ];
+        rv.symbols
+            .extend(letters.iter().map(|(c, s)| (*c, mask(s))));
+        rv
+    }
 
-        // parse input
-        let parts: Vec<&str> = line.split(" | ").collect();
-        let mut left: Vec<&str> = parts[0].split(" ").collect();
-        let right: Vec<&str> = parts[1].split(" ").collect();
+    fn fourteen_segment() -> Display {
+        Display::new(14, &FOURTEEN_SEGMENT)
+    }
 
-        // Sort the left by size (does this matter? who knows)
-        left.sort_by(|a, b| a.len().cmp(&b.len()));
+    // Sixteen segments split the top (0) and bottom (3) of the fourteen
+    // segment display in half, 14 and 15 being their right hand halves
+    fn sixteen_segment() -> Display {
+        let mut rv = Display::fourteen_segment();
+        rv.segments = 16;
+        for (_, lit) in rv.symbols.iter_mut() {
+            *lit |= (*lit & 1) << 14 | (*lit >> 3 & 1) << 15;
+        }
+        rv
+    }
 
-        // Digitize the segments
-        let digs: Vec<HashSet<&str>> = left
+    fn symbol(&self, lit: u32) -> Option<char> {
+        self.symbols
             .iter()
-            .map(|s| HashSet::from_iter(s.split("").filter(|f| *f != "")))
-            .collect();
+            .find(|(_, s)| *s == lit)
+            .map(|(c, _)| *c)
+    }
+}
 
-        // 0 = 1
-        // 1 = 7
-        // 2 = 4
-        // 3 = 2, 3, 5
-        // 4 = 2, 3, 5
-        // 5 = 2, 3, 5
-        // 6 = 0, 6, 9
-        // 7 = 0, 6, 9
-        // 8 = 0, 6, 9
-        // 9 = 8
-
-        // Top LED (0) is difference of first two
-        let top_segment: &str = &digs[1]
-            .difference(&digs[0])
-            .map(|f| *f)
-            .collect::<Vec<&str>>()[0];
-        known_segments.insert(&top_segment);
-        mappings.insert(top_segment, 0);
-
-        // Intersect to get bottom segment
-        let bottom_segment: &str = digs[3]
-            .iter()
-            .filter(|k| digs[4].contains(*k))
-            .filter(|k| digs[5].contains(*k))
-            .filter(|k| !digs[1].contains(*k))
-            .filter(|k| !digs[2].contains(*k))
-            .map(|k| *k)
-            .collect::<Vec<&str>>()[0];
-        known_segments.insert(&bottom_segment);
-
-        // Get middle segment by redoing half the work from above
-        let middle_segment: &str = digs[3]
-            .iter()
-            .filter(|k| digs[4].contains(*k))
-            .filter(|k| digs[5].contains(*k))
-            .filter(|k| *k != &top_segment && *k != &bottom_segment)
-            .map(|k| *k)
-            .collect::<Vec<&str>>()[0];
-        known_segments.insert(&middle_segment);
-
-        // Get bottom left by starting with everything and removing what we
-        // know and 4
-        let bottom_left_segment: &str = all_segments
-            .iter()
-            .filter(|k| !digs[2].contains(*k))
-            .filter(|k| !known_segments.contains(*k))
-            .map(|k| *k)
-            .collect::<Vec<&str>>()[0];
-        known_segments.insert(&bottom_left_segment);
-
-        // Find the top right by testing each of the 5-lengths until we get
-        // the 2
-        let mut top_right_segment: &str = "NOPE";
-        for idx in [3 as usize, 4, 5] {
-            let test = digs[idx]
-                .iter()
-                .filter(|k| !known_segments.contains(*k))
-                .map(|k| *k)
-                .collect::<Vec<&str>>();
-            if test.len() == 1 {
-                top_right_segment = test[0];
-            }
-        }
-        known_segments.insert(&top_right_segment);
+// Which wires a pattern lights, as a bit per wire. Sixteen wires is as
+// many as any of the displays has.
+fn wires(pattern: &str) -> Result<u32, String> {
+    pattern.chars().try_fold(0, |rv, c| match c {
+        'a'..='p' => Ok(rv | 1 << (c as u32 - 'a' as u32)),
+        _ => Err(format!("bad wire {:?} in {:?}", c, pattern)),
+    })
+}
 
-        // Find the bottom right by checking against 1
-        let bottom_right_segment: &str = digs[0]
-            .iter()
-            .filter(|k| !known_segments.contains(*k))
-            .map(|k| *k)
-            .collect::<Vec<&str>>()[0];
-        known_segments.insert(&bottom_right_segment);
+// wiring[wire] is the segment that wire drives
+type Wiring = Vec<usize>;
 
-        // Top left segment is whatever's left
-        let top_left_segment: &str = all_segments
-            .iter()
-            .filter(|k| !known_segments.contains(*k))
-            .map(|k| *k)
-            .collect::<Vec<&str>>()[0];
-        known_segments.insert(top_left_segment);
-
-        // Now we can set mappings and then numberize
-        mappings.insert(top_segment, 0);
-        mappings.insert(top_left_segment, 1);
-        mappings.insert(top_right_segment, 2);
-        mappings.insert(middle_segment, 3);
-        mappings.insert(bottom_left_segment, 4);
-        mappings.insert(bottom_right_segment, 5);
-        mappings.insert(bottom_segment, 6);
-
-        // Numberize the right side
-        let mut real_num: u32 = 0;
-        for idx in 0..4 {
-            let num = to_number(&mappings, right[idx]);
-            real_num += num as u32 * u32::pow(10, 3 - idx as u32);
+fn light(wiring: &[usize], wires: u32) -> u32 {
+    wiring
+        .iter()
+        .enumerate()
+        .filter(|(wire, _)| wires >> wire & 1 == 1)
+        .fold(0, |rv, (_, segment)| rv | 1 << segment)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Solution {
+    Unique(Wiring),
+    Ambiguous(Vec<Wiring>),
+    Inconsistent,
+}
+
+// Could the wires assigned so far still show a symbol for every pattern? A
+// pattern's lit wires that are assigned have to land inside a symbol of the
+// same size, and its dark ones outside it.
+fn consistent(wiring: &[usize], patterns: &[u32], display: &Display) -> bool {
+    let assigned = (1 << wiring.len()) - 1;
+    patterns.iter().all(|pattern| {
+        let lit = light(wiring, pattern & assigned);
+        let dark = light(wiring, !pattern & assigned);
+        display.symbols.iter().any(|(_, symbol)| {
+            symbol.count_ones() == pattern.count_ones() && lit & !symbol == 0 && dark & symbol == 0
+        })
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Deeper,
+    Skip,
+    Stop,
+}
+
+// Walks the wirings that turn all the patterns into symbols, assigning one
+// wire at a time and backing out as soon as a pattern can't work. `visit`
+// sees every wiring on the way, partly assigned ones too, and says whether
+// to go on below it. False if it got told to stop.
+fn search(
+    wiring: &mut Wiring,
+    patterns: &[u32],
+    display: &Display,
+    visit: &mut dyn FnMut(&[usize]) -> Step,
+) -> bool {
+    match visit(wiring) {
+        Step::Stop => return false,
+        Step::Skip => return true,
+        Step::Deeper => {}
+    }
+    for segment in 0..display.segments {
+        if wiring.contains(&segment) {
+            continue;
+        }
+        wiring.push(segment);
+        let more =
+            !consistent(wiring, patterns, display) || search(wiring, patterns, display, visit);
+        wiring.pop();
+        if !more {
+            return false;
         }
-        rv += real_num;
     }
+    true
+}
 
+// Up to `limit` of the wirings
+fn wirings(patterns: &[u32], display: &Display, limit: usize) -> Vec<Wiring> {
+    let mut rv = Vec::new();
+    search(&mut Vec::new(), patterns, display, &mut |wiring| {
+        if wiring.len() == display.segments {
+            rv.push(wiring.to_vec());
+            if rv.len() == limit {
+                return Step::Stop;
+            }
+        }
+        Step::Deeper
+    });
     rv
 }
 
-fn part_two_old(input: &str) -> u32 {
-    let mut rv = 0;
-    let all_segments: HashSet<&str> = HashSet::from_iter(["a", "b", "c", "d", "e", "f", "g"]);
-
-    for line in input.split("\n") {
-        // Set up the full set of unknowns (i.e., all segments map to all
-        // possible input wires)
-        let mut mappings: HashMap<u8, HashSet<&str>> = HashMap::new();
-        for idx in 0..7 {
-            mappings.insert(idx, all_segments.clone());
-        }
+// Only looks for a second wiring to prove it's ambiguous, the big displays
+// can have an enormous number of them
+fn solve(patterns: &[u32], display: &Display) -> Solution {
+    let mut found = wirings(patterns, display, 2);
+    match found.len() {
+        0 => Solution::Inconsistent,
+        1 => Solution::Unique(found.remove(0)),
+        _ => Solution::Ambiguous(found),
+    }
+}
 
-        // parse input
-        let parts: Vec<&str> = line.split(" | ").collect();
-        let mut left: Vec<&str> = parts[0].split(" ").collect();
-        let right: Vec<&str> = parts[1].split(" ").collect();
-
-        // Sort the left by size (does this matter? who knows)
-        left.sort_by(|a, b| a.len().cmp(&b.len()));
-
-        // Iterate each on the left and try to deduce the signal lighting
-        let mut seen_segments: HashSet<&str> = HashSet::new();
-        let mut unseen_segments = all_segments.clone();
-        for segment in left {
-            // Segments is which input segments are active (a, b, etc)
-            let mut segments = digitize(&segment);
-            unseen_segments.retain(|k| !segments.contains(*k));
-
-            // Remove input segments we've already seen (since we're going from less
-            // information to more information...)
-            dbg!(&segment, &segments);
-            segments.retain(|k| !seen_segments.contains(*k));
-
-            // Possibility is a digit this could be (0, 1, 2, etc)
-            for possibility in ids_to_possibles(&segment) {
-                // Lit segments are output lights we know are active (0, 1, 2, etc)
-                let mut lit_segments = digit_to_segments(possibility);
-
-                // Iterate each possibly lit segment and either intersect it with our
-                // seen segments if we know that it's lit, or the unseen segments if we
-                // know that it's dark
-                for test_segment in 0..7 {
-                    if lit_segments.contains(&test_segment) {
-                        // Lit
-                        mappings.entry(test_segment).and_modify(|m| {
-                            let hs_test = HashSet::from_iter(m.intersection(&segments).map(|m| *m));
-                            if hs_test.len() >= 1 {
-                                *m = hs_test;
-                            }
-                        });
-                    } else {
-                        // Unlit
-                        mappings.entry(test_segment).and_modify(|m| {
-                            let hs_test =
-                                HashSet::from_iter(m.intersection(&unseen_segments).map(|m| *m));
-                            if hs_test.len() >= 1 {
-                                *m = hs_test;
-                            }
-                        });
-                    }
+fn parse_line(line: &str) -> Result<(Vec<u32>, Vec<u32>), String> {
+    let (left, right) = line
+        .split_once('|')
+        .ok_or_else(|| format!("no | in {:?}", line))?;
+    Ok((
+        left.split_whitespace()
+            .map(wires)
+            .collect::<Result<_, _>>()?,
+        right
+            .split_whitespace()
+            .map(wires)
+            .collect::<Result<_, _>>()?,
+    ))
+}
+
+// Every distinct pattern on the line, and the outputs on their own
+fn read(line: &str, display: &Display) -> Result<(Vec<u32>, Vec<u32>), String> {
+    let (left, right) = parse_line(line)?;
+    let mut patterns = [left, right.clone()].concat();
+    patterns.sort();
+    patterns.dedup();
+    if patterns.iter().any(|p| p >> display.segments != 0) {
+        return Err(format!("{:?} has more wires than the display", line));
+    }
+    Ok((patterns, right))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reading {
+    Certain(char),
+    // Every symbol some consistent wiring shows, in display order
+    Ambiguous(Vec<char>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Partial {
+    // Whole wirings the search got to. That's all of them when complete,
+    // otherwise it found out early that the rest couldn't change the outputs.
+    wirings: u64,
+    complete: bool,
+    outputs: Vec<Reading>,
+}
+
+// Decodes from whatever patterns the line has, which needn't be all of them.
+// An output is only certain if every consistent wiring agrees on it, but
+// there can be billions of wirings on the big displays, so this only keeps
+// track of which symbols each output has shown. Once an output's wires are
+// all assigned its symbol is fixed, and there's no need to look any further
+// if every output's symbol is fixed and already seen, or if every output has
+// shown all the symbols its size allows.
+fn decode_partial(line: &str, display: &Display) -> Result<Partial, String> {
+    let (patterns, right) = read(line, display)?;
+
+    let mut shown = vec![vec![false; display.symbols.len()]; right.len()];
+    let mut unseen: usize = right
+        .iter()
+        .map(|pattern| {
+            display
+                .symbols
+                .iter()
+                .filter(|(_, s)| s.count_ones() == pattern.count_ones())
+                .count()
+        })
+        .sum();
+    let (mut count, mut skipped) = (0, false);
+    let finished = search(&mut Vec::new(), &patterns, display, &mut |wiring| {
+        let showing: Vec<Option<usize>> = right
+            .iter()
+            .map(|pattern| match pattern >> wiring.len() {
+                0 => {
+                    let lit = light(wiring, *pattern);
+                    display.symbols.iter().position(|(_, s)| *s == lit)
+                }
+                _ => None,
+            })
+            .collect();
+        if wiring.len() == display.segments {
+            count += 1;
+            for (output, symbol) in showing.iter().enumerate() {
+                let seen = &mut shown[output][symbol.unwrap()];
+                if !*seen {
+                    *seen = true;
+                    unseen -= 1;
                 }
             }
+            return if unseen == 0 {
+                Step::Stop
+            } else {
+                Step::Deeper
+            };
+        }
+        // Only once there's a whole wiring to show it can be done at all
+        let settled = showing
+            .iter()
+            .enumerate()
+            .all(|(output, symbol)| symbol.is_some_and(|s| shown[output][s]));
+        if count > 0 && settled {
+            skipped = true;
+            return Step::Skip;
+        }
+        Step::Deeper
+    });
+    if count == 0 {
+        return Err(format!("can't wire {:?}", line));
+    }
 
-            // Now we have "seen" these segments, record them forever
-            for segment in &segments {
-                seen_segments.insert(segment);
+    let outputs = shown
+        .iter()
+        .map(|seen| {
+            let mut symbols: Vec<char> = display
+                .symbols
+                .iter()
+                .zip(seen)
+                .filter(|(_, seen)| **seen)
+                .map(|((c, _), _)| *c)
+                .collect();
+            if symbols.len() == 1 {
+                Reading::Certain(symbols.remove(0))
+            } else {
+                Reading::Ambiguous(symbols)
             }
-            dbg!(&segments, &seen_segments, &unseen_segments, &mappings);
-        }
+        })
+        .collect();
+
+    Ok(Partial {
+        wirings: count,
+        complete: finished && !skipped,
+        outputs,
+    })
+}
+
+// The output side of a line as symbols, as long as it can only be read one
+// way even if the wiring itself is ambiguous
+fn decode(line: &str, display: &Display) -> Result<String, String> {
+    let (patterns, right) = read(line, display)?;
+
+    // Nearly always the case, and much cheaper than trying every wiring
+    if let Solution::Unique(wiring) = solve(&patterns, display) {
+        return Ok(right
+            .iter()
+            .map(|p| display.symbol(light(&wiring, *p)).unwrap())
+            .collect());
     }
 
-    rv
+    decode_partial(line, display)?
+        .outputs
+        .iter()
+        .map(|reading| match reading {
+            Reading::Certain(c) => Ok(*c),
+            Reading::Ambiguous(symbols) => Err(format!("{:?} could be any of {:?}", line, symbols)),
+        })
+        .collect()
+}
+
+fn part_two(input: &str) -> u32 {
+    let display = Display::seven_segment();
+    text::lines(input)
+        .iter()
+        .map(|line| decode(line, &display).unwrap().parse::<u32>().unwrap())
+        .sum()
 }
 
 fn main() {
     let input = include_str!("day8.txt");
     println!("PART ONE: {}", part_one(input));
     println!("PART TWO: {}", part_two(input));
+
+    // `decode <seven|hex|14|16>` decodes lines from stdin instead, for
+    // other kinds of display and for logs missing some of the patterns.
+    // Outputs that could be more than one symbol show as [a|b].
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|a| a.as_str()) == Some("decode") {
+        let display = match args.get(2).map(|a| a.as_str()) {
+            Some("seven") | None => Display::seven_segment(),
+            Some("hex") => Display::hexadecimal(),
+            Some("14") => Display::fourteen_segment(),
+            Some("16") => Display::sixteen_segment(),
+            Some(other) => panic!("unknown display {:?}", other),
+        };
+        for line in io::stdin().lock().lines() {
+            let line = line.unwrap();
+            if line.trim().is_empty() {
+                continue;
+            }
+            match decode_partial(line.trim(), &display) {
+                Ok(partial) => {
+                    let shown: Vec<String> = partial
+                        .outputs
+                        .iter()
+                        .map(|reading| match reading {
+                            Reading::Certain(c) => c.to_string(),
+                            Reading::Ambiguous(symbols) => {
+                                let symbols: Vec<String> =
+                                    symbols.iter().map(|c| c.to_string()).collect();
+                                format!("[{}]", symbols.join("|"))
+                            }
+                        })
+                        .collect();
+                    let wirings = match partial.complete {
+                        true => format!("wirings: {}", partial.wirings),
+                        false => format!("wirings: at least {}", partial.wirings),
+                    };
+                    println!("{} ({})", shown.concat(), wirings);
+                }
+                Err(e) => println!("ERROR: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use aoc2021::text::variants;
+
+    // The scrambled example from the puzzle, then a display wired straight through
+    const SAMPLE: &str =
+        "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf
+abcefg cf acdeg acdfg bcdf abdfg abdefg acf abcdefg abcdfg | cf bcdf acf abcdefg";
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), 4);
+            assert_eq!(part_two(&input), 5353 + 1478);
+        }
+    }
+
+    #[test]
+    fn solves_wirings() {
+        let (left, _) = parse_line(SAMPLE.lines().next().unwrap()).unwrap();
+        let Solution::Unique(wiring) = solve(&left, &Display::seven_segment()) else {
+            panic!("sample should have one wiring");
+        };
+        // d drives the top segment, as worked out in the puzzle
+        assert_eq!(wiring[3], 0);
+        assert_eq!(light(&wiring, wires("ab").unwrap()), 0b0100100);
+
+        // Just a 1 and a 7 leave most of the display open
+        let seven = Display::seven_segment();
+        assert_eq!(
+            wirings(
+                &[wires("ab").unwrap(), wires("abd").unwrap()],
+                &seven,
+                usize::MAX
+            )
+            .len(),
+            2 * 24
+        );
+        assert!(matches!(
+            solve(&[wires("ab").unwrap(), wires("abd").unwrap()], &seven),
+            Solution::Ambiguous(_)
+        ));
+
+        // Two different 1s can't both be right
+        assert_eq!(
+            solve(
+                &[wires("ab").unwrap(), wires("cd").unwrap()],
+                &Display::seven_segment()
+            ),
+            Solution::Inconsistent
+        );
+    }
+
+    // Shows every symbol of the display through a scrambled wiring, then
+    // the message, the way the puzzle lines look
+    fn scramble(display: &Display, wiring: &[usize], message: &str) -> String {
+        let pattern = |lit: u32| -> String {
+            (0..display.segments)
+                .filter(|wire| lit >> wiring[*wire] & 1 == 1)
+                .map(|wire| (b'a' + wire as u8) as char)
+                .collect()
+        };
+        let all: Vec<String> = display.symbols.iter().map(|(_, s)| pattern(*s)).collect();
+        let shown: Vec<String> = message
+            .chars()
+            .map(|c| pattern(display.symbols.iter().find(|(s, _)| *s == c).unwrap().1))
+            .collect();
+        format!("{} | {}", all.join(" "), shown.join(" "))
+    }
+
+    #[test]
+    fn decodes_other_displays() {
+        for display in [
+            Display::seven_segment(),
+            Display::hexadecimal(),
+            Display::fourteen_segment(),
+            Display::sixteen_segment(),
+        ] {
+            let mut masks: Vec<u32> = display.symbols.iter().map(|(_, s)| *s).collect();
+            masks.sort();
+            masks.dedup();
+            assert_eq!(masks.len(), display.symbols.len());
+            assert!(masks.iter().all(|m| *m < 1 << display.segments));
+        }
+
+        let hex = Display::hexadecimal();
+        let line = scramble(&hex, &[3, 6, 0, 5, 1, 4, 2], "bEEF");
+        assert_eq!(decode(&line, &hex), Ok("bEEF".to_string()));
+
+        let fourteen = Display::fourteen_segment();
+        let wiring = [7, 12, 0, 3, 9, 1, 13, 5, 10, 2, 8, 6, 4, 11];
+        let line = scramble(&fourteen, &wiring, "HELLO");
+        let (left, right) = parse_line(&line).unwrap();
+        assert_eq!(
+            solve(&[left, right].concat(), &fourteen),
+            Solution::Unique(wiring.to_vec())
+        );
+        assert_eq!(decode(&line, &fourteen), Ok("HELLO".to_string()));
+
+        // Every symbol lights both halves of the top and bottom together, so
+        // those wires can be swapped without changing what's shown
+        let sixteen = Display::sixteen_segment();
+        let wiring = [7, 12, 0, 3, 9, 1, 13, 5, 10, 2, 8, 6, 4, 11, 15, 14];
+        let line = scramble(&sixteen, &wiring, "W0RLD");
+        let (left, _) = parse_line(&line).unwrap();
+        assert_eq!(wirings(&left, &sixteen, usize::MAX).len(), 4);
+        assert_eq!(decode(&line, &sixteen), Ok("W0RLD".to_string()));
+    }
+
+    #[test]
+    fn decodes_with_missing_patterns() {
+        let seven = Display::seven_segment();
+
+        // Only the 1 from the first sample line, so the 5 next to it only
+        // has to miss one of the 1's segments
+        let partial = decode_partial("ab | cdfeb ab", &seven).unwrap();
+        assert!(partial.wirings > 1);
+        assert_eq!(
+            partial.outputs,
+            vec![Reading::Ambiguous(vec!['2', '5']), Reading::Certain('1')]
+        );
+        assert!(decode("ab | cdfeb ab", &seven).is_err());
+
+        // With the 4 as well there's enough to tell the 5 and 3 apart
+        assert_eq!(
+            decode("ab eafb | cdfeb fcadb ab eafb", &seven),
+            Ok("5314".to_string())
+        );
+
+        // Nothing on the left at all, the output alone pins down an 8 and a 7
+        let partial = decode_partial("| abcdefg dab", &seven).unwrap();
+        assert_eq!(
+            partial.outputs,
+            vec![Reading::Certain('8'), Reading::Certain('7')]
+        );
+        // Those are the only symbols that size, so one wiring settles it
+        assert_eq!(partial.wirings, 1);
+        assert!(!partial.complete);
+
+        // The last wire decides the output, so every wiring gets counted: the
+        // 1 either way round, then the rest of a 2 or a 5
+        let partial = decode_partial("ab | bcdeg", &seven).unwrap();
+        assert_eq!(partial.outputs, vec![Reading::Ambiguous(vec!['2', '5'])]);
+        assert_eq!(partial.wirings, 2 * 24);
+        assert!(partial.complete);
+
+        assert!(decode_partial("ab cd | ab", &seven).is_err());
+    }
+
+    #[test]
+    fn decodes_huge_numbers_of_wirings() {
+        // Billions of wirings, but every three segment symbol turns up quickly
+        let partial = decode_partial("abc | abc", &Display::fourteen_segment()).unwrap();
+        assert_eq!(
+            partial.outputs,
+            vec![Reading::Ambiguous(vec!['1', '7', 'L', 'T', 'Y'])]
+        );
+        assert!(!partial.complete);
+        // and no two segment symbol ever can
+        assert_eq!(
+            decode_partial("| abc ab", &Display::sixteen_segment()),
+            Err("can't wire \"| abc ab\"".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        let seven = Display::seven_segment();
+        assert_eq!(
+            decode_partial("ab cd", &seven),
+            Err("no | in \"ab cd\"".to_string())
+        );
+        assert_eq!(
+            decode("ab | aB", &seven),
+            Err("bad wire 'B' in \"aB\"".to_string())
+        );
+        assert_eq!(wires("a{"), Err("bad wire '{' in \"a{\"".to_string()));
+        assert_eq!(
+            decode_partial("ab | abh", &seven),
+            Err("\"ab | abh\" has more wires than the display".to_string())
+        );
+        assert_eq!(wires("ap"), Ok(1 | 1 << 15));
+    }
 }
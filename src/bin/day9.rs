@@ -1,20 +1,82 @@
+#[cfg(feature = "bench")]
+use aoc2021::bench;
+#[cfg(feature = "render")]
+use aoc2021::render::Canvas;
+use aoc2021::text;
+#[cfg(feature = "tui")]
+use aoc2021::tui;
 use std::collections::HashMap;
+use std::env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Connectivity {
+    // Up, down, left and right
+    Four,
+    // Diagonals too
+    Eight,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Basin {
+    id: u32,
+    low_point: (u32, u32),
+    // Cells that only drain here
+    size: u32,
+    // From the low point up to where the basin spills over
+    depth: u8,
+    // Water it holds before spilling
+    volume: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Watershed {
+    basins: Vec<Basin>,
+    // Cells that drain to more than one basin, and which ones
+    ridges: Vec<((u32, u32), Vec<u32>)>,
+}
 
 struct Cave {
     cells: Vec<Vec<u8>>,
+    // Anything this high or higher separates basins
+    wall: u8,
+    connectivity: Connectivity,
     basin_id: HashMap<u32, u32>,
     basin_size: HashMap<u32, u32>,
 }
 
 impl Cave {
-    fn new() -> Cave {
+    fn new(wall: u8, connectivity: Connectivity) -> Cave {
         Cave {
             cells: Vec::new(),
+            wall,
+            connectivity,
             basin_id: HashMap::new(),
             basin_size: HashMap::new(),
         }
     }
 
+    fn neighbours(self: &Cave, x: u32, y: u32) -> Vec<(u32, u32)> {
+        let offsets: &[(i32, i32)] = match self.connectivity {
+            Connectivity::Four => &[(-1, 0), (0, -1), (1, 0), (0, 1)],
+            Connectivity::Eight => &[
+                (-1, 0),
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+                (0, 1),
+                (-1, 1),
+            ],
+        };
+        offsets
+            .iter()
+            .map(|(dx, dy)| (x as i32 + dx, y as i32 + dy))
+            .filter(|(ix, iy)| self.get_cell(*ix, *iy).is_some())
+            .map(|(ix, iy)| (ix as u32, iy as u32))
+            .collect()
+    }
+
     fn get_cell(self: &Cave, ix: i32, iy: i32) -> Option<u8> {
         if iy < 0
             || iy >= self.cells.len() as i32
@@ -27,56 +89,194 @@ impl Cave {
     }
 
     fn is_low_point(self: &Cave, x: u32, y: u32) -> bool {
-        let ix = x as i32;
-        let iy = y as i32;
-
-        let points = [
-            self.get_cell(ix - 1, iy),
-            self.get_cell(ix, iy - 1),
-            self.get_cell(ix + 1, iy),
-            self.get_cell(ix, iy + 1),
-        ];
+        let self_height = self.get_cell(x as i32, y as i32).unwrap();
+        self.neighbours(x, y)
+            .iter()
+            .all(|(nx, ny)| self.cells[*ny as usize][*nx as usize] > self_height)
+    }
+
+    fn height_at(self: &Cave, (x, y): (u32, u32)) -> u8 {
+        self.cells[y as usize][x as usize]
+    }
+
+    fn is_wall(self: &Cave, x: u32, y: u32) -> bool {
+        self.cells[y as usize][x as usize] >= self.wall
+    }
 
-        let self_height = self.get_cell(ix, iy).unwrap();
+    // Floods every basin from its first cell with an explicit stack, so the
+    // size of the map is only limited by memory. Ids count up from 1.
+    fn label_basins(self: &mut Cave) {
+        let mut next_id = 1;
+        let mut stack = Vec::new();
 
-        for point in points {
-            match point {
-                Some(height) => {
-                    if height <= self_height {
-                        return false;
+        for y in 0..self.cells.len() as u32 {
+            for x in 0..self.cells[y as usize].len() as u32 {
+                if self.is_wall(x, y) || self.get_basin_id(x as i32, y as i32) != Some(0) {
+                    continue;
+                }
+
+                self.set_basin_id(x, y, next_id);
+                stack.push((x, y));
+                while let Some((cx, cy)) = stack.pop() {
+                    for (nx, ny) in self.neighbours(cx, cy) {
+                        if !self.is_wall(nx, ny)
+                            && self.get_basin_id(nx as i32, ny as i32) == Some(0)
+                        {
+                            self.set_basin_id(nx, ny, next_id);
+                            stack.push((nx, ny));
+                        }
                     }
                 }
-                _ => {}
+                aoc2021::trace!(
+                    "basin {} from {},{}: {} cells",
+                    next_id,
+                    x,
+                    y,
+                    self.basin_size[&next_id]
+                );
+                next_id += 1;
             }
         }
-
-        true
     }
 
-    fn start_new_basin(self: &mut Cave, x: u32, y: u32) {
-        let basin_id = *self.basin_size.keys().max().unwrap_or(&0) + 1;
-        self.infect_or_exit(x as i32, y as i32, basin_id);
-    }
+    // Steepest descent from every cell, walls included. A cell whose
+    // steepest neighbours lead to different basins is a ridge and stays out
+    // of the basin maps. Flat areas are settled a plateau at a time, lowest
+    // first so whatever they drain into is already labelled: a plateau with
+    // nothing lower next to it is one basin, with its first cell as the low
+    // point, otherwise each cell follows the nearest way off it.
+    fn watershed(self: &mut Cave) -> Watershed {
+        self.basin_id.clear();
+        self.basin_size.clear();
+
+        let width = self.cells[0].len() as u32;
+        let addr = |(x, y): (u32, u32)| (y * width + x) as usize;
+
+        // Every run of same height cells, in raster order of their first
+        // cell and breadth first from there
+        let mut plateau_of: Vec<Option<usize>> = vec![None; self.cells.len() * width as usize];
+        let mut plateaus: Vec<Vec<(u32, u32)>> = Vec::new();
+        for y in 0..self.cells.len() as u32 {
+            for x in 0..width {
+                if plateau_of[addr((x, y))].is_some() {
+                    continue;
+                }
+                let id = plateaus.len();
+                plateau_of[addr((x, y))] = Some(id);
+                let mut cells = vec![(x, y)];
+                let mut idx = 0;
+                while idx < cells.len() {
+                    for n in self.neighbours(cells[idx].0, cells[idx].1) {
+                        if plateau_of[addr(n)].is_none()
+                            && self.height_at(n) == self.height_at((x, y))
+                        {
+                            plateau_of[addr(n)] = Some(id);
+                            cells.push(n);
+                        }
+                    }
+                    idx += 1;
+                }
+                plateaus.push(cells);
+            }
+        }
+        plateaus.sort_by_key(|cells| self.height_at(cells[0]));
 
-    fn infect_or_exit(self: &mut Cave, ix: i32, iy: i32, basin_id: u32) {
-        if self.get_cell(ix, iy).unwrap_or(9) == 9 {
-            // No cell or it's a wall, move on
-            return;
+        let mut drains: Vec<Option<Vec<u32>>> = vec![None; plateau_of.len()];
+        let mut low_points: Vec<(u32, u32)> = Vec::new();
+        let mut ridges = Vec::new();
+        for cells in plateaus {
+            let level = self.height_at(cells[0]);
+            let mut settled: Vec<((u32, u32), Vec<u32>)> = Vec::new();
+            for cell in &cells {
+                let neighbours = self.neighbours(cell.0, cell.1);
+                let lowest = neighbours.iter().map(|n| self.height_at(*n)).min();
+                if let Some(lowest) = lowest.filter(|l| *l < level) {
+                    let targets = neighbours
+                        .iter()
+                        .filter(|n| self.height_at(**n) == lowest)
+                        .flat_map(|n| drains[addr(*n)].clone().unwrap())
+                        .collect();
+                    settled.push((*cell, targets));
+                }
+            }
+            if settled.is_empty() {
+                low_points.push(cells[0]);
+                let id = low_points.len() as u32;
+                settled = cells.iter().map(|c| (*c, vec![id])).collect();
+            }
+
+            // Spread in from the edges a step at a time, so each cell drains
+            // wherever its closest ways off the plateau do
+            while !settled.is_empty() {
+                let mut next: Vec<((u32, u32), Vec<u32>)> = Vec::new();
+                for (cell, mut targets) in settled {
+                    targets.sort();
+                    targets.dedup();
+                    if drains[addr(cell)].is_some() {
+                        continue;
+                    }
+                    match targets.len() {
+                        1 => self.set_basin_id(cell.0, cell.1, targets[0]),
+                        _ => ridges.push((cell, targets.clone())),
+                    }
+                    for n in self.neighbours(cell.0, cell.1) {
+                        if plateau_of[addr(n)] == plateau_of[addr(cell)] {
+                            next.push((n, targets.clone()));
+                        }
+                    }
+                    drains[addr(cell)] = Some(targets);
+                }
+                // Merge what reached the same cell from different sides
+                next.retain(|(cell, _)| drains[addr(*cell)].is_none());
+                next.sort();
+                settled = Vec::new();
+                for (cell, targets) in next {
+                    match settled.last_mut() {
+                        Some((last, merged)) if *last == cell => merged.extend(targets),
+                        _ => settled.push((cell, targets)),
+                    }
+                }
+            }
         }
 
-        if self.get_basin_id(ix, iy).unwrap_or(0) > 0 {
-            // Already infected, don't recurse forever
-            return;
+        // The lowest ridge cell is where a basin spills over, a basin with
+        // none holds water up to its highest cell. The map edge is a wall.
+        let mut rims: HashMap<u32, u8> = HashMap::new();
+        for ((x, y), basins) in &ridges {
+            for id in basins {
+                let rim = rims.entry(*id).or_insert(u8::MAX);
+                *rim = (*rim).min(self.cells[*y as usize][*x as usize]);
+            }
+        }
+        let mut highest: HashMap<u32, u8> = HashMap::new();
+        for (addr, id) in &self.basin_id {
+            let height = self.cells[(addr / width) as usize][(addr % width) as usize];
+            let top = highest.entry(*id).or_insert(0);
+            *top = (*top).max(height);
+        }
+        let rim = |id: &u32| *rims.get(id).unwrap_or(&highest[id]);
+        let mut volume: HashMap<u32, u64> = HashMap::new();
+        for (addr, id) in &self.basin_id {
+            let height = self.cells[(addr / width) as usize][(addr % width) as usize];
+            *volume.entry(*id).or_insert(0) += rim(id).saturating_sub(height) as u64;
         }
 
-        println!("Infected {}, {} with {}", ix, iy, basin_id);
-        self.set_basin_id(ix as u32, iy as u32, basin_id);
+        let basins = low_points
+            .iter()
+            .enumerate()
+            .map(|(idx, (x, y))| {
+                let id = idx as u32 + 1;
+                Basin {
+                    id,
+                    low_point: (*x, *y),
+                    size: self.basin_size[&id],
+                    depth: rim(&id) - self.cells[*y as usize][*x as usize],
+                    volume: volume[&id],
+                }
+            })
+            .collect();
 
-        // Begin infecting from neighboring points
-        self.infect_or_exit(ix - 1, iy, basin_id);
-        self.infect_or_exit(ix, iy - 1, basin_id);
-        self.infect_or_exit(ix + 1, iy, basin_id);
-        self.infect_or_exit(ix, iy + 1, basin_id);
+        Watershed { basins, ridges }
     }
 
     fn set_basin_id(self: &mut Cave, x: u32, y: u32, basin_id: u32) {
@@ -99,24 +299,79 @@ impl Cave {
         Some(*self.basin_id.get(&addr).unwrap_or(&0))
     }
 
-    fn print_basin_map(self: &Cave) {
-        for y in 0..self.cells.len() as u32 {
-            println!("");
-            for x in 0..self.cells[y as usize].len() as u32 {
-                let basin_id = self.get_basin_id(x as i32, y as i32).unwrap_or(9);
-                print!("{}", basin_id);
+    // Basin ids, biggest first
+    fn largest_ids(self: &Cave) -> Vec<u32> {
+        let mut rv: Vec<u32> = self.basin_size.keys().copied().collect();
+        rv.sort_by_key(|id| (std::cmp::Reverse(self.basin_size[id]), *id));
+        rv
+    }
+
+    // Height shades every cell from dark low points to light ridges. The
+    // three biggest basins get strong colours, the rest a muted one by id,
+    // and cells outside any basin stay grey.
+    #[cfg(any(feature = "render", feature = "tui"))]
+    fn color(self: &Cave, x: u32, y: u32, highlighted: &[u32]) -> [u8; 3] {
+        const STRONG: [[u8; 3]; 3] = [[230, 60, 50], [60, 200, 80], [60, 110, 240]];
+        const MUTED: [[u8; 3]; 4] = [
+            [150, 130, 90],
+            [110, 140, 150],
+            [140, 110, 150],
+            [120, 150, 110],
+        ];
+
+        let height = self.cells[y as usize][x as usize] as u32;
+        let id = self.get_basin_id(x as i32, y as i32).unwrap_or(0);
+        let base = match highlighted.iter().position(|h| *h == id) {
+            _ if id == 0 => [200, 200, 200],
+            Some(rank) => STRONG[rank],
+            None => MUTED[id as usize % MUTED.len()],
+        };
+        // 40% brightness at height 0 up to full at 9 and above
+        let shade = 40 + 60 * height.min(9) / 9;
+        base.map(|c| (c as u32 * shade / 100) as u8)
+    }
+
+    #[cfg(feature = "render")]
+    fn render(self: &Cave, scale: usize) -> Canvas {
+        let highlighted: Vec<u32> = self.largest_ids().into_iter().take(3).collect();
+        let mut canvas = Canvas::new(self.cells[0].len() * scale, self.cells.len() * scale);
+        for (y, row) in self.cells.iter().enumerate() {
+            for x in 0..row.len() {
+                let color = self.color(x as u32, y as u32, &highlighted);
+                for py in 0..scale {
+                    for px in 0..scale {
+                        canvas.set(x * scale + px, y * scale + py, color);
+                    }
+                }
             }
         }
+        canvas
+    }
+
+    // Two spaces per cell so it comes out roughly square
+    #[cfg(feature = "tui")]
+    fn draw(self: &Cave) -> String {
+        let highlighted: Vec<u32> = self.largest_ids().into_iter().take(3).collect();
+        let mut rv = String::new();
+        for (y, row) in self.cells.iter().enumerate() {
+            for x in 0..row.len() {
+                rv.push_str(&tui::bg("  ", self.color(x as u32, y as u32, &highlighted)));
+            }
+            rv.push('\n');
+        }
+        rv
     }
 }
 
-fn parse_cave(input: &str) -> Cave {
-    let mut cave = Cave::new();
-    for line in input.split("\n") {
+fn parse_cave(input: &str, wall: u8, connectivity: Connectivity) -> Cave {
+    let mut cave = Cave::new(wall, connectivity);
+    for line in text::lines(input) {
         let points: Vec<u8> = line
-            .split("")
-            .filter(|f| *f != "")
-            .map(|n| n.parse::<u8>().unwrap())
+            .bytes()
+            .map(|b| {
+                assert!(b.is_ascii_digit(), "bad height {:?}", b as char);
+                b - b'0'
+            })
             .collect();
         cave.cells.push(points);
     }
@@ -124,7 +379,7 @@ fn parse_cave(input: &str) -> Cave {
 }
 
 fn part_one(input: &str) -> u32 {
-    let cave = parse_cave(input);
+    let cave = parse_cave(input, 9, Connectivity::Four);
 
     let mut risk: u32 = 0;
     for y in 0..cave.cells.len() {
@@ -138,34 +393,260 @@ fn part_one(input: &str) -> u32 {
     risk
 }
 
+fn largest_basins(input: &str, wall: u8, connectivity: Connectivity) -> u32 {
+    let mut cave = parse_cave(input, wall, connectivity);
+    cave.label_basins();
+
+    // Fewer than three basins just multiplies what there is
+    cave.largest_ids()
+        .iter()
+        .take(3)
+        .map(|id| cave.basin_size[id])
+        .product()
+}
+
 fn part_two(input: &str) -> u32 {
-    let mut cave = parse_cave(input);
+    largest_basins(input, 9, Connectivity::Four)
+}
+
+fn main() {
+    let input = include_str!("day9.txt");
+    println!("PART ONE: {}", part_one(input));
+    println!("PART TWO: {}", part_two(input));
+
+    // `basins <4|8> [wall]` relabels with other connectivity or wall height
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|a| a.as_str()) == Some("basins") {
+        let connectivity = match args.get(2).map(|a| a.as_str()) {
+            Some("8") => Connectivity::Eight,
+            _ => Connectivity::Four,
+        };
+        let wall = args.get(3).map_or(9, |a| a.parse::<u8>().unwrap());
+        println!("BASINS: {}", largest_basins(input, wall, connectivity));
+    }
 
-    for y in 0..cave.cells.len() as u32 {
-        for x in 0..cave.cells[y as usize].len() as u32 {
-            cave.start_new_basin(x, y);
+    // `watershed <4|8>` lists basins by volume with steepest descent instead
+    // of walls
+    if args.get(1).map(|a| a.as_str()) == Some("watershed") {
+        let connectivity = match args.get(2).map(|a| a.as_str()) {
+            Some("8") => Connectivity::Eight,
+            _ => Connectivity::Four,
+        };
+        let mut cave = parse_cave(input, 9, connectivity);
+        let mut shed = cave.watershed();
+        shed.basins.sort_by_key(|b| std::cmp::Reverse(b.volume));
+        for basin in &shed.basins {
+            println!(
+                "BASIN {}: low point {:?} size {} depth {} volume {}",
+                basin.id, basin.low_point, basin.size, basin.depth, basin.volume
+            );
         }
+        println!("RIDGES: {}", shed.ridges.len());
     }
 
-    let mut sorted_basin_ids: Vec<u32> = cave.basin_size.keys().map(|f| *f).collect();
-    sorted_basin_ids.sort_by(|a, b| {
-        cave.basin_size
-            .get(b)
-            .unwrap()
-            .cmp(cave.basin_size.get(a).unwrap())
-    });
+    // `render <path> [watershed]` writes the basins out as a PPM, four
+    // pixels to a cell
+    #[cfg(feature = "render")]
+    if args.get(1).map(|a| a.as_str()) == Some("render") {
+        let mut cave = parse_cave(input, 9, Connectivity::Four);
+        if args.get(3).map(|a| a.as_str()) == Some("watershed") {
+            cave.watershed();
+        } else {
+            cave.label_basins();
+        }
+        let path = args.get(2).map_or("day9.ppm", |a| a.as_str());
+        match cave.render(4).save(std::path::Path::new(path)) {
+            Ok(()) => println!("RENDERED: {}", path),
+            Err(e) => println!("FAILED: {}", e),
+        }
+    }
 
-    // cave.print_basin_map();
+    // `draw [watershed]` does the same in the terminal
+    #[cfg(feature = "tui")]
+    if args.get(1).map(|a| a.as_str()) == Some("draw") {
+        let mut cave = parse_cave(input, 9, Connectivity::Four);
+        if args.get(2).map(|a| a.as_str()) == Some("watershed") {
+            cave.watershed();
+        } else {
+            cave.label_basins();
+        }
+        print!("{}", cave.draw());
+    }
 
-    let mut rv: u32 = 1;
-    rv *= cave.basin_size.get(&sorted_basin_ids[0]).unwrap();
-    rv *= cave.basin_size.get(&sorted_basin_ids[1]).unwrap();
-    rv *= cave.basin_size.get(&sorted_basin_ids[2]).unwrap();
-    rv
+    // `bench [runs]` times both ways of finding basins
+    #[cfg(feature = "bench")]
+    if args.get(1).map(|a| a.as_str()) == Some("bench") {
+        let runs = args.get(2).map_or(10, |a| a.parse::<u32>().unwrap());
+        let (_, timing) = bench::measure("label", runs, || {
+            let mut cave = parse_cave(input, 9, Connectivity::Four);
+            cave.label_basins();
+            cave.basin_size.len()
+        });
+        println!("BENCH: {}", timing);
+        let (_, timing) = bench::measure("watershed", runs, || {
+            parse_cave(input, 9, Connectivity::Four).watershed()
+        });
+        println!("BENCH: {}", timing);
+    }
 }
 
-fn main() {
-    let input = include_str!("day9.txt");
-    println!("PART ONE: {}", part_one(input));
-    println!("PART TWO: {}", part_two(input));
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use aoc2021::text::variants;
+
+    const SAMPLE: &str = "2199943210\n3987894921\n9856789892\n8767896789\n9899965678";
+
+    #[test]
+    fn it_works() {
+        for input in variants(SAMPLE) {
+            assert_eq!(part_one(&input), 15);
+            assert_eq!(part_two(&input), 1134);
+        }
+    }
+
+    #[test]
+    fn labels_with_other_rules() {
+        // Every basin touches another one across a corner
+        assert_eq!(largest_basins(SAMPLE, 9, Connectivity::Eight), 35);
+        // Nothing is a wall, so it's all one basin
+        assert_eq!(largest_basins(SAMPLE, 10, Connectivity::Four), 50);
+
+        let mut cave = parse_cave(SAMPLE, 8, Connectivity::Four);
+        cave.label_basins();
+        // Eights wall things off too, shrinking every basin
+        let mut sizes: Vec<u32> = cave.basin_size.values().copied().collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![3, 6, 7, 9]);
+        assert_eq!(cave.get_basin_id(0, 0), Some(1));
+        assert_eq!(cave.get_basin_id(2, 0), Some(0));
+    }
+
+    #[test]
+    fn labels_huge_maps() {
+        // A million cells in one winding basin used to blow the stack
+        let row = "1".repeat(1000);
+        let wall = format!("{}1", "9".repeat(999));
+        let mut lines = Vec::new();
+        for idx in 0..1000 {
+            lines.push(if idx % 2 == 0 {
+                row.clone()
+            } else {
+                wall.clone()
+            });
+        }
+        let mut cave = parse_cave(&lines.join("\n"), 9, Connectivity::Four);
+        cave.label_basins();
+        assert_eq!(cave.basin_size.len(), 1);
+        assert_eq!(cave.basin_size[&1], 500 * 1000 + 500);
+    }
+
+    #[test]
+    fn finds_watersheds() {
+        let mut cave = parse_cave("10201", 9, Connectivity::Four);
+        let shed = cave.watershed();
+        assert_eq!(
+            shed.basins,
+            vec![
+                Basin {
+                    id: 1,
+                    low_point: (1, 0),
+                    size: 2,
+                    depth: 2,
+                    volume: 3
+                },
+                Basin {
+                    id: 2,
+                    low_point: (3, 0),
+                    size: 2,
+                    depth: 2,
+                    volume: 3
+                },
+            ]
+        );
+        assert_eq!(shed.ridges, vec![((2, 0), vec![1, 2])]);
+        assert_eq!(cave.get_basin_id(2, 0), Some(0));
+
+        // Flat bottoms are one basin, and with nowhere to spill it fills up
+        // to its highest cell
+        let mut cave = parse_cave("311\n211\n999", 9, Connectivity::Four);
+        let shed = cave.watershed();
+        assert_eq!(shed.basins.len(), 1);
+        assert_eq!(shed.basins[0].size, 9);
+        assert_eq!(shed.basins[0].depth, 8);
+        assert_eq!(shed.basins[0].volume, 6 + 7 + 8 * 4);
+    }
+
+    #[test]
+    fn drains_plateaus() {
+        // The flat top drains off its lower end instead of being a low point
+        let mut cave = parse_cave("551", 9, Connectivity::Four);
+        let shed = cave.watershed();
+        assert_eq!(shed.basins.len(), 1);
+        assert_eq!(shed.basins[0].low_point, (2, 0));
+        assert_eq!(shed.basins[0].size, 3);
+        assert!(shed.ridges.is_empty());
+
+        // A U shaped floor is one basin however it's scanned
+        let mut cave = parse_cave("191\n111", 9, Connectivity::Four);
+        let shed = cave.watershed();
+        assert_eq!(shed.basins.len(), 1);
+        assert_eq!(shed.basins[0].size, 6);
+        assert!(shed.ridges.is_empty());
+
+        // Cells split between the nearest ways off, the middle one is a tie
+        let mut cave = parse_cave("0555550", 9, Connectivity::Four);
+        let shed = cave.watershed();
+        assert_eq!(shed.ridges, vec![((3, 0), vec![1, 2])]);
+        assert_eq!(cave.get_basin_id(2, 0), Some(1));
+        assert_eq!(cave.get_basin_id(4, 0), Some(2));
+    }
+
+    #[test]
+    fn sheds_the_sample() {
+        let mut cave = parse_cave(SAMPLE, 9, Connectivity::Four);
+        let shed = cave.watershed();
+        // Same low points as part one, and every cell is accounted for
+        assert_eq!(shed.basins.len(), 4);
+        for basin in &shed.basins {
+            assert!(cave.is_low_point(basin.low_point.0, basin.low_point.1));
+        }
+        let sized: u32 = shed.basins.iter().map(|b| b.size).sum();
+        assert_eq!(sized as usize + shed.ridges.len(), 50);
+        assert!(shed.ridges.iter().all(|(_, basins)| basins.len() > 1));
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn renders_basins() {
+        let mut cave = parse_cave(SAMPLE, 9, Connectivity::Four);
+        cave.label_basins();
+        let canvas = cave.render(2);
+        assert_eq!((canvas.width, canvas.height), (20, 10));
+
+        // Biggest basin is red, the walls grey, all shaded by height
+        let biggest = cave.largest_ids()[0];
+        assert_eq!(cave.get_basin_id(2, 2), Some(biggest));
+        // Height 5 is at 73% brightness
+        assert_eq!(canvas.get(4, 4), Some([167, 43, 36]));
+        assert_eq!(canvas.get(5, 5), canvas.get(4, 4));
+        assert_eq!(canvas.get(4, 0), Some([200, 200, 200]));
+    }
+
+    #[test]
+    #[cfg(feature = "tui")]
+    fn draws_basins() {
+        let mut cave = parse_cave("09\n19", 9, Connectivity::Four);
+        cave.label_basins();
+        assert_eq!(
+            cave.draw(),
+            format!(
+                "{}{}\n{}{}\n",
+                tui::bg("  ", [92, 24, 20]),
+                tui::bg("  ", [200, 200, 200]),
+                tui::bg("  ", [105, 27, 23]),
+                tui::bg("  ", [200, 200, 200])
+            )
+        );
+    }
 }
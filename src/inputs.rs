@@ -0,0 +1,265 @@
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::answers::data_dir;
+
+pub fn fingerprint(contents: &str) -> String {
+    hex::encode(Sha256::digest(contents.as_bytes()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    CarriageReturns(usize),
+    TrailingNewlines(usize),
+    BlankLines(Vec<usize>),
+    TrailingWhitespace(Vec<usize>),
+    ByteOrderMark,
+    Empty,
+    FingerprintMismatch { recorded: String, actual: String },
+}
+
+impl Issue {
+    pub fn describe(&self) -> String {
+        match self {
+            Issue::CarriageReturns(count) => format!("{} CRLF line endings", count),
+            Issue::TrailingNewlines(count) => format!("{} trailing newline(s)", count),
+            Issue::BlankLines(lines) => format!("blank lines at {:?}", lines),
+            Issue::TrailingWhitespace(lines) => format!("trailing whitespace at {:?}", lines),
+            Issue::ByteOrderMark => "starts with a byte order mark".to_string(),
+            Issue::Empty => "empty".to_string(),
+            Issue::FingerprintMismatch { recorded, actual } => {
+                format!("fingerprint changed from {} to {}", recorded, actual)
+            }
+        }
+    }
+}
+
+// Looks for the things that used to make our `split("\n")` parsers fall
+// over. The days read through `text` now so they cope, but the stored copy
+// should still be the file exactly as downloaded. Line numbers are 1-based,
+// and a single blank line is fine since a few days use it between sections.
+pub fn check_contents(contents: &str) -> Vec<Issue> {
+    let mut rv = Vec::new();
+
+    if contents.is_empty() {
+        rv.push(Issue::Empty);
+        return rv;
+    }
+    if contents.starts_with('\u{feff}') {
+        rv.push(Issue::ByteOrderMark);
+    }
+
+    let crlf = contents.matches("\r\n").count();
+    if crlf > 0 {
+        rv.push(Issue::CarriageReturns(crlf));
+    }
+
+    let body = contents.trim_end_matches(['\r', '\n']);
+    let trailing = contents[body.len()..].matches('\n').count();
+    if trailing > 0 {
+        rv.push(Issue::TrailingNewlines(trailing));
+    }
+
+    let mut blank = Vec::new();
+    let mut whitespace = Vec::new();
+    let lines: Vec<&str> = body.split('\n').map(|l| l.trim_end_matches('\r')).collect();
+    for (idx, line) in lines.iter().enumerate() {
+        if line.is_empty() {
+            // Doubled up blank lines are a mistake, single ones are sections
+            if idx > 0 && lines[idx - 1].is_empty() {
+                blank.push(idx + 1);
+            }
+        } else if line.ends_with([' ', '\t']) {
+            whitespace.push(idx + 1);
+        }
+    }
+    if !blank.is_empty() {
+        rv.push(Issue::BlankLines(blank));
+    }
+    if !whitespace.is_empty() {
+        rv.push(Issue::TrailingWhitespace(whitespace));
+    }
+
+    rv
+}
+
+// Puzzle inputs, one directory per user with a dayN.txt per day. The
+// fingerprints live next to the answer database so both travel together.
+#[derive(Debug, Clone)]
+pub struct InputStore {
+    pub dir: PathBuf,
+    pub user: String,
+    pub fingerprints: PathBuf,
+}
+
+impl InputStore {
+    pub fn new(dir: &Path, user: &str, fingerprints: &Path) -> InputStore {
+        InputStore {
+            dir: dir.to_path_buf(),
+            user: user.to_string(),
+            fingerprints: fingerprints.to_path_buf(),
+        }
+    }
+
+    pub fn from_env() -> InputStore {
+        let dir = match env::var("AOC_INPUT_DIR") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => data_dir().join("inputs"),
+        };
+        let user = env::var("AOC_USER").unwrap_or("default".to_string());
+        InputStore::new(&dir, &user, &data_dir().join("fingerprints.tsv"))
+    }
+
+    pub fn path(&self, day: u8) -> PathBuf {
+        self.dir.join(&self.user).join(format!("day{}.txt", day))
+    }
+
+    pub fn read(&self, day: u8) -> Result<String, String> {
+        let path = self.path(day);
+        fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // Copies an input into the store and remembers its fingerprint
+    pub fn import(&self, day: u8, contents: &str) -> Result<String, String> {
+        // Read the fingerprints before touching anything, so a bad file
+        // can't leave a new input next to its old digest
+        let mut recorded = self.load_fingerprints()?;
+
+        let path = self.path(day);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&path, contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        let digest = fingerprint(contents);
+        recorded.retain(|(user, d, _)| !(*user == self.user && *d == day));
+        recorded.push((self.user.clone(), day, digest.clone()));
+        recorded.sort();
+        self.save_fingerprints(&recorded)?;
+
+        Ok(digest)
+    }
+
+    pub fn recorded_fingerprint(&self, day: u8) -> Result<Option<String>, String> {
+        Ok(self
+            .load_fingerprints()?
+            .into_iter()
+            .find(|(user, d, _)| *user == self.user && *d == day)
+            .map(|(_, _, digest)| digest))
+    }
+
+    pub fn days(&self) -> Vec<u8> {
+        (1..=25).filter(|day| self.path(*day).exists()).collect()
+    }
+
+    pub fn check(&self, day: u8) -> Result<Vec<Issue>, String> {
+        let contents = self.read(day)?;
+        let mut rv = check_contents(&contents);
+
+        if let Some(recorded) = self.recorded_fingerprint(day)? {
+            let actual = fingerprint(&contents);
+            if recorded != actual {
+                rv.push(Issue::FingerprintMismatch { recorded, actual });
+            }
+        }
+
+        Ok(rv)
+    }
+
+    fn load_fingerprints(&self) -> Result<Vec<(String, u8, String)>, String> {
+        let contents = match fs::read_to_string(&self.fingerprints) {
+            Ok(contents) => contents,
+            // Only a missing file means nothing's recorded yet, otherwise the
+            // next import would save over every other fingerprint
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("{}: {}", self.fingerprints.display(), e)),
+        };
+
+        let mut rv = Vec::new();
+        for (idx, line) in contents.lines().enumerate() {
+            let parts: Vec<&str> = line.split('\t').collect();
+            match (parts.len(), parts.get(1).and_then(|d| d.parse::<u8>().ok())) {
+                (3, Some(day)) => rv.push((parts[0].to_string(), day, parts[2].to_string())),
+                _ => {
+                    return Err(format!(
+                        "{}:{}: malformed fingerprint",
+                        self.fingerprints.display(),
+                        idx + 1
+                    ))
+                }
+            }
+        }
+        Ok(rv)
+    }
+
+    fn save_fingerprints(&self, recorded: &[(String, u8, String)]) -> Result<(), String> {
+        if let Some(parent) = self.fingerprints.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut out = String::new();
+        for (user, day, digest) in recorded {
+            out += &format!("{}\t{}\t{}\n", user, day, digest);
+        }
+        fs::write(&self.fingerprints, out).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_corruption() {
+        assert_eq!(check_contents("1\n2\n3"), vec![]);
+        assert_eq!(check_contents("a\n\nb"), vec![]);
+        assert_eq!(
+            check_contents("1\r\n2\r\n"),
+            vec![Issue::CarriageReturns(2), Issue::TrailingNewlines(1)]
+        );
+        assert_eq!(
+            check_contents("1 \n\n\n2\n\n"),
+            vec![
+                Issue::TrailingNewlines(2),
+                Issue::BlankLines(vec![3]),
+                Issue::TrailingWhitespace(vec![1])
+            ]
+        );
+    }
+
+    #[test]
+    fn fingerprints_imports() {
+        let root = env::temp_dir().join(format!("aoc2021-inputs-{}", std::process::id()));
+        let store = InputStore::new(&root.join("inputs"), "me", &root.join("fingerprints.tsv"));
+
+        let digest = store.import(7, "16,1,2,0,4,2,7,1,2,14").unwrap();
+        assert_eq!(digest.len(), 64);
+        assert_eq!(store.days(), vec![7]);
+        assert_eq!(store.check(7).unwrap(), vec![]);
+
+        fs::write(store.path(7), "16,1,2,0,4,2,7,1,2,14\n").unwrap();
+        let issues = store.check(7).unwrap();
+        assert_eq!(issues[0], Issue::TrailingNewlines(1));
+        assert!(matches!(issues[1], Issue::FingerprintMismatch { .. }));
+
+        // An unreadable fingerprint file stops the import instead of being
+        // replaced
+        fs::write(root.join("fingerprints.tsv"), b"me\t7\t\xff\n").unwrap();
+        assert!(store.import(7, "1,2,3").is_err());
+        assert!(store.import(8, "1,2,3").is_err());
+        assert_eq!(
+            fs::read_to_string(store.path(7)).unwrap(),
+            "16,1,2,0,4,2,7,1,2,14\n"
+        );
+        assert!(!store.path(8).exists());
+        assert_eq!(
+            fs::read(root.join("fingerprints.tsv")).unwrap(),
+            b"me\t7\t\xff\n".to_vec()
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,19 @@
+// Shared bits that the day binaries and the `aoc` helper use. The days
+// themselves still live in src/bin/ as standalone programs.
+
+pub mod answers;
+pub mod bigint;
+pub mod inputs;
+pub mod rng;
+pub mod sonar;
+pub mod submit;
+pub mod text;
+pub mod trace;
+
+// Optional subsystems, see the features in Cargo.toml
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(feature = "render")]
+pub mod render;
+#[cfg(feature = "tui")]
+pub mod tui;
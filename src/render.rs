@@ -0,0 +1,67 @@
+use std::fs;
+use std::path::Path;
+
+pub type Rgb = [u8; 3];
+
+// A plain RGB image that can be written out as a binary PPM, which every
+// image viewer understands and needs no dependencies to produce
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![[0, 0, 0]; width * height],
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        if x < self.width && y < self.height {
+            Some(self.pixels[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut rv = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for pixel in &self.pixels {
+            rv.extend_from_slice(pixel);
+        }
+        rv
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_ppm()).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_ppm() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set(1, 0, [255, 0, 10]);
+        canvas.set(5, 5, [1, 1, 1]);
+        assert_eq!(canvas.get(1, 0), Some([255, 0, 10]));
+        assert_eq!(canvas.get(2, 0), None);
+        assert_eq!(
+            canvas.to_ppm(),
+            b"P6\n2 1\n255\n\x00\x00\x00\xff\x00\x0a".to_vec()
+        );
+    }
+}
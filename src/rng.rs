@@ -0,0 +1,76 @@
+// Small seeded random numbers (SplitMix64) for the simulations, so a run can
+// be repeated exactly from its seed. Not for anything that needs to be
+// unpredictable.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in 0..bound, bound must not be zero
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound != 0, "bound must not be zero");
+        // Throw away the top sliver that would make the low values likelier
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+
+    // Fisher-Yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            let other = self.below(idx as u64 + 1) as usize;
+            items.swap(idx, other);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_from_seed() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let first: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..5).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(first[0], Rng::new(43).next_u64());
+        assert!((0..1000).all(|_| a.below(7) < 7));
+    }
+
+    #[test]
+    fn shuffles_everything() {
+        let mut rng = Rng::new(1);
+        let mut items: Vec<u32> = (0..100).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..100).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..100).collect::<Vec<_>>());
+
+        // Every position is reachable
+        let mut seen = [false; 3];
+        for _ in 0..100 {
+            let mut items = [0, 1, 2];
+            rng.shuffle(&mut items);
+            seen[items[0]] = true;
+        }
+        assert_eq!(seen, [true; 3]);
+    }
+}
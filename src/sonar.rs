@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+use std::env;
+use std::io::{self, BufRead};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Increase,
+    Decrease,
+    Plateau,
+}
+
+impl Comparison {
+    fn matches(&self, previous: u64, current: u64) -> bool {
+        match self {
+            Comparison::Increase => current > previous,
+            Comparison::Decrease => current < previous,
+            Comparison::Plateau => current == previous,
+        }
+    }
+}
+
+// A streak of consecutive window comparisons that all matched, `start` is
+// the position of the first one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    pub start: usize,
+    pub len: usize,
+}
+
+// Positions and runs are only filled in when the analyzer was asked to
+// record them, the rest is always there
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Sweep {
+    pub count: usize,
+    pub readings: usize,
+    // Index of the reading that completes each matching window
+    pub positions: Vec<usize>,
+    pub runs: Vec<Run>,
+    // First of the longest if there's a tie
+    pub longest: Option<Run>,
+}
+
+impl Sweep {
+    pub fn longest_run(&self) -> Option<Run> {
+        self.longest
+    }
+}
+
+// Compares the sum of each window of readings with the window one reading
+// earlier. The two windows share everything but their ends, so that's the
+// same as comparing the reading coming in with the one falling out.
+#[derive(Debug)]
+pub struct Analyzer {
+    window: usize,
+    comparison: Comparison,
+    recent: VecDeque<u64>,
+    record: bool,
+    current: Option<Run>,
+    sweep: Sweep,
+}
+
+impl Analyzer {
+    pub fn new(window: usize, comparison: Comparison) -> Analyzer {
+        assert!(window > 0, "window must hold at least one reading");
+        Analyzer {
+            window,
+            comparison,
+            recent: VecDeque::with_capacity(window + 1),
+            record: true,
+            current: None,
+            sweep: Sweep::default(),
+        }
+    }
+
+    // Only keeps the window and a few counters, for logs too big to hold the
+    // positions of every change
+    pub fn counting(window: usize, comparison: Comparison) -> Analyzer {
+        let mut rv = Analyzer::new(window, comparison);
+        rv.record = false;
+        rv
+    }
+
+    pub fn push(&mut self, depth: u64) {
+        let position = self.sweep.readings;
+        self.sweep.readings += 1;
+        self.recent.push_back(depth);
+        if self.recent.len() <= self.window {
+            return;
+        }
+
+        let leaving = self.recent.pop_front().unwrap();
+        if !self.comparison.matches(leaving, depth) {
+            return;
+        }
+
+        self.sweep.count += 1;
+        let run = match self.current {
+            Some(run) if run.start + run.len == position => Run {
+                start: run.start,
+                len: run.len + 1,
+            },
+            _ => Run {
+                start: position,
+                len: 1,
+            },
+        };
+        self.current = Some(run);
+        if self.sweep.longest.is_none_or(|l| run.len > l.len) {
+            self.sweep.longest = Some(run);
+        }
+
+        if self.record {
+            self.sweep.positions.push(position);
+            match self.sweep.runs.last_mut() {
+                Some(last) if last.start == run.start => *last = run,
+                _ => self.sweep.runs.push(run),
+            }
+        }
+    }
+
+    pub fn finish(self) -> Sweep {
+        self.sweep
+    }
+}
+
+pub fn analyze(depths: &[u64], window: usize, comparison: Comparison) -> Sweep {
+    let mut analyzer = Analyzer::new(window, comparison);
+    for depth in depths {
+        analyzer.push(*depth);
+    }
+    analyzer.finish()
+}
+
+// Reads one depth per line without ever holding more than the window and
+// the current line, blank lines are skipped
+pub fn stream<R: BufRead>(
+    mut reader: R,
+    window: usize,
+    comparison: Comparison,
+) -> Result<Sweep, String> {
+    let mut analyzer = Analyzer::counting(window, comparison);
+    let mut line = String::new();
+    let mut line_number = 0;
+
+    loop {
+        line.clear();
+        line_number += 1;
+        let read = reader.read_line(&mut line).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+
+        let reading = line.trim_start_matches('\u{feff}').trim();
+        if reading.is_empty() {
+            continue;
+        }
+        let depth = reading
+            .parse::<u64>()
+            .map_err(|_| format!("line {}: bad depth {:?}", line_number, reading))?;
+        analyzer.push(depth);
+    }
+
+    Ok(analyzer.finish())
+}
+
+// Day 1 counts increases with a window of one reading for part one and
+// three for part two. The puzzle input goes through `stream` as well, so it
+// gets the same parsing as stdin does.
+pub fn increases(input: &str, window: usize) -> Result<Sweep, String> {
+    stream(input.as_bytes(), window, Comparison::Increase)
+}
+
+pub fn report(sweep: &Sweep) -> Vec<String> {
+    let mut rv = vec![format!("ANSWER: {}", sweep.count)];
+    if let Some(run) = sweep.longest_run() {
+        rv.push(format!(
+            "LONGEST RUN: {} increases from reading {}",
+            run.len, run.start
+        ));
+    }
+    rv
+}
+
+// Everything both day 1 binaries do, given their window. A `-` argument
+// reads the depths from stdin a line at a time instead, for sonar logs far
+// bigger than the puzzle input.
+pub fn run(input: &str, window: usize) {
+    let sweep = if env::args().nth(1).as_deref() == Some("-") {
+        stream(io::stdin().lock(), window, Comparison::Increase)
+    } else {
+        increases(input, window)
+    };
+    match sweep {
+        Ok(sweep) => {
+            for line in report(&sweep) {
+                println!("{}", line);
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Cursor, Read};
+
+    const SAMPLE: [u64; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn counts_windows() {
+        let sweep = analyze(&SAMPLE, 1, Comparison::Increase);
+        assert_eq!(sweep.count, 7);
+        assert_eq!(sweep.positions, vec![1, 2, 3, 5, 6, 7, 9]);
+        assert_eq!(
+            sweep.runs,
+            vec![
+                Run { start: 1, len: 3 },
+                Run { start: 5, len: 3 },
+                Run { start: 9, len: 1 }
+            ]
+        );
+        assert_eq!(sweep.longest_run(), Some(Run { start: 1, len: 3 }));
+
+        assert_eq!(analyze(&SAMPLE, 3, Comparison::Increase).count, 5);
+        assert_eq!(
+            analyze(&SAMPLE, 1, Comparison::Decrease).positions,
+            vec![4, 8]
+        );
+        // 199+200 vs 200+208 and so on
+        assert_eq!(
+            analyze(&SAMPLE, 2, Comparison::Decrease).positions,
+            vec![4, 5, 9]
+        );
+        assert_eq!(
+            analyze(&SAMPLE, 10, Comparison::Increase),
+            Sweep {
+                readings: 10,
+                ..Sweep::default()
+            }
+        );
+    }
+
+    #[test]
+    fn zero_is_a_reading() {
+        assert_eq!(analyze(&[0, 1, 2], 1, Comparison::Increase).count, 2);
+        assert_eq!(
+            analyze(&[5, 0, 0, 0], 1, Comparison::Plateau).positions,
+            vec![2, 3]
+        );
+        assert_eq!(
+            analyze(&[0, 0, 0, 0, 1], 3, Comparison::Increase).positions,
+            vec![4]
+        );
+    }
+
+    // Produces `0\n1\n2\n...` on the fly, so the test never holds the log
+    struct Counting {
+        next: u64,
+        end: u64,
+        pending: Vec<u8>,
+    }
+
+    impl Read for Counting {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            while self.pending.len() < buf.len() && self.next < self.end {
+                self.pending
+                    .extend_from_slice(format!("{}\n", self.next % 1000).as_bytes());
+                self.next += 1;
+            }
+            let n = buf.len().min(self.pending.len());
+            buf[..n].copy_from_slice(&self.pending[..n]);
+            self.pending.drain(..n);
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn streams_readers() {
+        let log = "199\r\n200\r\n208\r\n210\r\n\r\n200\r\n207\r\n240\r\n269\r\n260\r\n263\r\n";
+        let sweep = stream(Cursor::new(log), 3, Comparison::Increase).unwrap();
+        assert_eq!(sweep.count, 5);
+        assert_eq!(sweep.readings, 10);
+        assert_eq!(sweep.positions, vec![]);
+        assert_eq!(
+            sweep.longest,
+            analyze(&SAMPLE, 3, Comparison::Increase).longest
+        );
+
+        let err = stream(Cursor::new("1\n2\nthree\n"), 1, Comparison::Increase);
+        assert_eq!(err, Err("line 3: bad depth \"three\"".to_string()));
+
+        // A million readings counting 0..999 over and over
+        let reader = Counting {
+            next: 0,
+            end: 1_000_000,
+            pending: Vec::new(),
+        };
+        let sweep = stream(BufReader::new(reader), 1, Comparison::Increase).unwrap();
+        assert_eq!(sweep.readings, 1_000_000);
+        assert_eq!(sweep.count, 999_000);
+        assert_eq!(sweep.longest, Some(Run { start: 1, len: 999 }));
+    }
+
+    #[test]
+    fn reports_day_one() {
+        let input = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263";
+        assert_eq!(
+            report(&increases(input, 1).unwrap()),
+            vec!["ANSWER: 7", "LONGEST RUN: 3 increases from reading 1"]
+        );
+        assert_eq!(report(&increases("", 3).unwrap()), vec!["ANSWER: 0"]);
+        assert!(increases("1\nx", 1).is_err());
+    }
+}
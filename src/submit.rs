@@ -0,0 +1,306 @@
+use std::env;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use crate::answers::{AnswerDb, Verdict};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    // The server judged the answer, and we recorded it
+    Judged(Verdict),
+    // We didn't send anything, the database already knows the verdict
+    AlreadyCorrect(String),
+    KnownWrong(Verdict),
+    // The server refused to judge it, nothing is recorded
+    TooRecent,
+    WrongLevel,
+    Unrecognized(String),
+}
+
+// Talks to anything that speaks the Advent of Code answer form. Only plain
+// http is supported, which is all a local stand-in server needs.
+#[derive(Debug, Clone)]
+pub struct Client {
+    pub endpoint: String,
+    pub year: u16,
+    pub session: String,
+}
+
+impl Client {
+    pub fn new(endpoint: &str, year: u16, session: &str) -> Client {
+        Client {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            year,
+            session: session.to_string(),
+        }
+    }
+
+    pub fn from_env() -> Client {
+        let endpoint = env::var("AOC_ENDPOINT").unwrap_or("http://localhost:8080".to_string());
+        let year = env::var("AOC_YEAR")
+            .ok()
+            .and_then(|y| y.parse::<u16>().ok())
+            .unwrap_or(2021);
+        let session = env::var("AOC_SESSION").unwrap_or_default();
+        Client::new(&endpoint, year, &session)
+    }
+
+    pub fn post_answer(&self, day: u8, part: u8, answer: &str) -> Result<Outcome, String> {
+        let path = format!("/{}/day/{}/answer", self.year, day);
+        let form = format!("level={}&answer={}", part, form_encode(answer.trim()));
+        let (status, body) = self.post(&path, &form)?;
+        if status != 200 {
+            return Err(format!("server returned HTTP {}", status));
+        }
+        Ok(parse_response(&body))
+    }
+
+    fn post(&self, path: &str, form: &str) -> Result<(u16, String), String> {
+        let rest = match self.endpoint.strip_prefix("http://") {
+            Some(rest) => rest,
+            None => return Err(format!("unsupported endpoint {}", self.endpoint)),
+        };
+        let (host, prefix) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], &rest[idx..]),
+            None => (rest, ""),
+        };
+        let addr = if host.contains(':') {
+            host.to_string()
+        } else {
+            format!("{}:80", host)
+        };
+
+        let mut stream = TcpStream::connect(&addr).map_err(|e| format!("{}: {}", addr, e))?;
+        stream
+            .set_read_timeout(Some(Duration::from_secs(30)))
+            .map_err(|e| e.to_string())?;
+
+        let request = format!(
+            "POST {}{} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Cookie: session={}\r\n\
+             Content-Type: application/x-www-form-urlencoded\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n\
+             {}",
+            prefix,
+            path,
+            host,
+            self.session,
+            form.len(),
+            form
+        );
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|e| e.to_string())?;
+
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).map_err(|e| e.to_string())?;
+        parse_http_response(&raw)
+    }
+}
+
+// Where `needle` first starts in `haystack`
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+// Works on the raw bytes, chunk sizes count bytes and a chunk can end in
+// the middle of a character, so the body only becomes text at the end
+fn parse_http_response(raw: &[u8]) -> Result<(u16, String), String> {
+    let split = find(raw, b"\r\n\r\n").ok_or("truncated HTTP response")?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .and_then(|l| l.split(' ').nth(1))
+        .and_then(|s| s.parse::<u16>().ok())
+        .ok_or("malformed HTTP status line")?;
+
+    let chunked = lines.any(|l| {
+        let l = l.to_ascii_lowercase();
+        l.starts_with("transfer-encoding:") && l.contains("chunked")
+    });
+    if !chunked {
+        return Ok((status, String::from_utf8_lossy(body).into_owned()));
+    }
+
+    // Undo chunked encoding, each chunk is a hex size line then the data
+    let mut rv = Vec::new();
+    let mut rest = body;
+    while let Some(end) = find(rest, b"\r\n") {
+        let line = String::from_utf8_lossy(&rest[..end]);
+        let size = usize::from_str_radix(line.split(';').next().unwrap().trim(), 16)
+            .map_err(|_| "malformed chunk size")?;
+        let after = &rest[end + 2..];
+        if size == 0 || after.len() < size {
+            break;
+        }
+        rv.extend_from_slice(&after[..size]);
+        rest = after[size..]
+            .strip_prefix(b"\r\n")
+            .unwrap_or(&after[size..]);
+    }
+    Ok((status, String::from_utf8_lossy(&rv).into_owned()))
+}
+
+fn form_encode(input: &str) -> String {
+    let mut rv = String::new();
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                rv.push(byte as char)
+            }
+            _ => rv += &format!("%{:02X}", byte),
+        }
+    }
+    rv
+}
+
+// Works out what the answer page is telling us, the phrasing here is the
+// same as the real site uses
+pub fn parse_response(body: &str) -> Outcome {
+    if body.contains("That's the right answer") {
+        Outcome::Judged(Verdict::Correct)
+    } else if body.contains("That's not the right answer") {
+        if body.contains("your answer is too high") {
+            Outcome::Judged(Verdict::TooHigh)
+        } else if body.contains("your answer is too low") {
+            Outcome::Judged(Verdict::TooLow)
+        } else {
+            Outcome::Judged(Verdict::Incorrect)
+        }
+    } else if body.contains("You gave an answer too recently") {
+        Outcome::TooRecent
+    } else if body.contains("You don't seem to be solving the right level") {
+        Outcome::WrongLevel
+    } else {
+        Outcome::Unrecognized(body.trim().chars().take(200).collect())
+    }
+}
+
+// Submits an answer unless the database already knows how it'll go, and
+// records whatever verdict comes back
+pub fn submit(
+    db: &mut AnswerDb,
+    client: &Client,
+    day: u8,
+    part: u8,
+    answer: &str,
+) -> Result<Outcome, String> {
+    let answer = answer.trim();
+    if answer.is_empty() {
+        return Err("refusing to submit an empty answer".to_string());
+    }
+    if let Some(correct) = db.correct_answer(day, part) {
+        return Ok(Outcome::AlreadyCorrect(correct.to_string()));
+    }
+    if let Some(record) = db.known_wrong(day, part, answer) {
+        return Ok(Outcome::KnownWrong(record.verdict));
+    }
+
+    let outcome = client.post_answer(day, part, answer)?;
+    if let Outcome::Judged(verdict) = outcome {
+        db.record(day, part, answer, verdict);
+        db.save()?;
+    }
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // Answers one request with the given page and hands back what it saw
+    fn mock_server(page: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let mut request = String::new();
+            loop {
+                let n = stream.read(&mut buf).unwrap();
+                request += &String::from_utf8_lossy(&buf[..n]);
+                if n == 0 || request.contains("answer=") {
+                    break;
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                page.len(),
+                page
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+            request
+        });
+        (endpoint, handle)
+    }
+
+    #[test]
+    fn submits_and_records() {
+        let path = env::temp_dir().join(format!("aoc2021-submit-{}.tsv", std::process::id()));
+        let mut db = AnswerDb::open(&path).unwrap();
+        let (endpoint, server) =
+            mock_server("<p>That's not the right answer; your answer is too high.</p>");
+        let client = Client::new(&endpoint, 2021, "cookie");
+
+        let outcome = submit(&mut db, &client, 4, 2, "5000").unwrap();
+        assert_eq!(outcome, Outcome::Judged(Verdict::TooHigh));
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /2021/day/4/answer HTTP/1.1"));
+        assert!(request.contains("Cookie: session=cookie"));
+        assert!(request.ends_with("level=2&answer=5000"));
+
+        // No server this time, a bigger answer must never leave the machine
+        let outcome = submit(&mut db, &client, 4, 2, "6000").unwrap();
+        assert_eq!(outcome, Outcome::KnownWrong(Verdict::TooHigh));
+
+        let db = AnswerDb::open(&path).unwrap();
+        assert_eq!(db.records(4, 2).len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn parses_responses() {
+        assert_eq!(
+            parse_response("That's the right answer! You are one gold star closer."),
+            Outcome::Judged(Verdict::Correct)
+        );
+        assert_eq!(
+            parse_response("That's not the right answer; your answer is too low."),
+            Outcome::Judged(Verdict::TooLow)
+        );
+        assert_eq!(
+            parse_response("That's not the right answer. If you're stuck..."),
+            Outcome::Judged(Verdict::Incorrect)
+        );
+        assert_eq!(
+            parse_response("You gave an answer too recently; wait 30s."),
+            Outcome::TooRecent
+        );
+    }
+
+    #[test]
+    fn decodes_chunked_bodies() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+        assert_eq!(
+            parse_http_response(raw).unwrap(),
+            (200, "hello world".to_string())
+        );
+
+        // A chunk boundary in the middle of an é used to panic
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n1\r\n\xC3\r\n1\r\n\xA9\r\n0\r\n\r\n";
+        assert_eq!(
+            parse_http_response(raw).unwrap(),
+            (200, "\u{e9}".to_string())
+        );
+    }
+}
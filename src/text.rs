@@ -0,0 +1,86 @@
+// Every day reads its input through here, so CRLF line endings, a trailing
+// newline or a stray byte order mark look the same as a clean file to the
+// parsers.
+
+// The input with any byte order mark and trailing whitespace removed, for
+// the days whose input is a single line
+pub fn trimmed(input: &str) -> &str {
+    input.trim_start_matches('\u{feff}').trim_end()
+}
+
+// All the non-blank lines, without their line endings or trailing whitespace
+pub fn lines(input: &str) -> Vec<&str> {
+    trimmed(input)
+        .lines()
+        .map(|l| l.trim_end())
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+// Groups of lines separated by one or more blank lines
+pub fn sections(input: &str) -> Vec<Vec<&str>> {
+    let mut rv = Vec::new();
+    let mut section = Vec::new();
+
+    for line in trimmed(input).lines().map(|l| l.trim_end()) {
+        if line.is_empty() {
+            if !section.is_empty() {
+                rv.push(section);
+                section = Vec::new();
+            }
+            continue;
+        }
+        section.push(line);
+    }
+    if !section.is_empty() {
+        rv.push(section);
+    }
+
+    rv
+}
+
+// A separated list like `3,4,3,1,2`, fields are trimmed and empty ones dropped
+pub fn fields(input: &str, separator: char) -> Vec<&str> {
+    trimmed(input)
+        .split(separator)
+        .map(|f| f.trim())
+        .filter(|f| !f.is_empty())
+        .collect()
+}
+
+// The same input the way different editors and downloads tend to mangle it,
+// the first one is always the input as given. Used by the day tests.
+pub fn variants(input: &str) -> Vec<String> {
+    let crlf = input.replace('\n', "\r\n");
+    vec![
+        input.to_string(),
+        format!("{}\n", input),
+        format!("{}\n\n", input),
+        crlf.clone(),
+        format!("{}\r\n", crlf),
+        format!("\u{feff}{}\n", input),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_lines_and_sections() {
+        for input in variants("1\n2\n\n3") {
+            assert_eq!(lines(&input), vec!["1", "2", "3"]);
+            assert_eq!(sections(&input), vec![vec!["1", "2"], vec!["3"]]);
+        }
+        assert_eq!(sections("a\n\n\n\nb\n"), vec![vec!["a"], vec!["b"]]);
+        assert_eq!(lines(""), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn splits_fields() {
+        for input in variants("3,4,3,1,2") {
+            assert_eq!(fields(&input, ','), vec!["3", "4", "3", "1", "2"]);
+        }
+        assert_eq!(trimmed("\u{feff}D2FE28\r\n"), "D2FE28");
+    }
+}
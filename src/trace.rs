@@ -0,0 +1,34 @@
+// Debug output for the days. Without the `trace` feature the arguments are
+// still type checked, so nothing goes stale, but it all compiles away.
+
+#[cfg(feature = "trace")]
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        eprintln!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if false {
+            eprintln!($($arg)*)
+        }
+    };
+}
+
+pub fn enabled() -> bool {
+    cfg!(feature = "trace")
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn traces() {
+        let value = 5;
+        crate::trace!("value is {}", value);
+        assert_eq!(super::enabled(), cfg!(feature = "trace"));
+    }
+}
@@ -0,0 +1,29 @@
+// Just enough ANSI escape handling to draw coloured grids in a terminal
+
+pub const RESET: &str = "\x1b[0m";
+pub const CLEAR: &str = "\x1b[2J\x1b[H";
+
+pub fn fg(text: &str, color: [u8; 3]) -> String {
+    format!(
+        "\x1b[38;2;{};{};{}m{}{}",
+        color[0], color[1], color[2], text, RESET
+    )
+}
+
+pub fn bg(text: &str, color: [u8; 3]) -> String {
+    format!(
+        "\x1b[48;2;{};{};{}m{}{}",
+        color[0], color[1], color[2], text, RESET
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_in_escapes() {
+        assert_eq!(fg("9", [1, 2, 3]), "\x1b[38;2;1;2;3m9\x1b[0m");
+        assert_eq!(bg(" ", [0, 0, 255]), "\x1b[48;2;0;0;255m \x1b[0m");
+    }
+}